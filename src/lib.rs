@@ -1,15 +1,50 @@
 #![crate_name = "vec3"]
-
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::num::ParseFloatError;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Axis {
     X,
     Y,
     Z,
 }
 
+impl Axis {
+    // Returns every axis in order, which is handy for looping over
+    // the components of a vector, e.g.
+    // `for axis in Axis::all() { v.component(axis); }`
+    pub fn all() -> [Axis; 3] {
+        return [Axis::X, Axis::Y, Axis::Z];
+    }
+
+    // Returns the next axis in cyclic order, X -> Y -> Z -> X.
+    pub fn next(self) -> Axis {
+        match self {
+            Axis::X => Axis::Y,
+            Axis::Y => Axis::Z,
+            Axis::Z => Axis::X,
+        }
+    }
+
+    // Returns the two axes other than this one, in cyclic order.
+    // For example `Axis::Y.other_two()` returns `(Axis::Z, Axis::X)`.
+    pub fn other_two(self) -> (Axis, Axis) {
+        let next = self.next();
+        return (next, next.next());
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[repr(C)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -40,6 +75,26 @@ impl Vec3 {
         Vec3 { x: x, y: y, z: z }
     }
 
+    // Builds a vector from f64 components by narrowing each one to
+    // f32. Values that can't be represented exactly as an f32 are
+    // rounded to the nearest one, and values beyond f32's range
+    // become infinite.
+    pub fn from_f64_array(a: [f64; 3]) -> Vec3 {
+        return Vec3::new(a[0] as f32, a[1] as f32, a[2] as f32);
+    }
+
+    // Returns the unit vector in the XZ plane at `radians` from +X,
+    // i.e. `(cos θ, 0, sin θ)`. Handy for top-down headings.
+    pub fn from_angle_xz(radians: f32) -> Vec3 {
+        return Vec3::new(radians.cos(), 0.0, radians.sin());
+    }
+
+    // Returns the unit vector in the XY plane at `radians` from +X,
+    // i.e. `(cos θ, sin θ, 0)`.
+    pub fn from_angle_xy(radians: f32) -> Vec3 {
+        return Vec3::new(radians.cos(), radians.sin(), 0.0);
+    }
+
     // Returns the component of this vector along the specified
     // axis. For example, `some_vec.component(Axis::X)` returns
     // `some_vec.x`.
@@ -68,6 +123,16 @@ impl Vec3 {
         }
     }
 
+    // Returns the component at `index`, wrapping around modulo 3 so
+    // that index 3 is `x` again, 4 is `y` and so on. Never panics.
+    pub fn component_wrapped(&self, index: usize) -> f32 {
+        match index % 3 {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+
     // Returns a new copy of self with the x-value replaced
     // with the specified value.
     pub fn with_x(self, x: f32) -> Vec3 {
@@ -102,6 +167,16 @@ impl Vec3 {
         self / self.length()
     }
 
+    // Like `normalize`, but returns an error instead of a vector of
+    // NaNs when this vector has zero length.
+    pub fn checked_normalize(self) -> Result<Vec3, NormalizeError> {
+        let length = self.length();
+        if length == 0.0 {
+            return Err(NormalizeError);
+        }
+        return Ok(self / length);
+    }
+
     pub fn length(&self) -> f32 {
         return self.length_squared().sqrt();
     }
@@ -137,367 +212,3549 @@ impl Vec3 {
             z: a.x * b.y - a.y * b.x,
         };
     }
-}
 
-// This macro helps us implement math operators on Vector3
-// in such a way that it handles binary operators on any
-// combination of Vec3, &Vec3 and f32.
-macro_rules! impl_binary_operations {
-  // $VectorType is something like `Vec3`
-  // $Operation is something like `Add`
-  // $op_fn is something like `add`
-  // $op_symbol is something like `+`
-  ($VectorType:ident $Operation:ident $op_fn:ident $op_symbol:tt) => {
-    // Implement a + b where a and b are both of type &VectorType.
-    // Lower down we'll implement cases where either a or b - or both
-    // - are values by forwarding through to this implementation.
-    impl<'a, 'b> $Operation<&'a $VectorType> for &'b $VectorType {
-      type Output = $VectorType;
-      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
-        $VectorType {
-          x: self.x $op_symbol other.x,
-          y: self.y $op_symbol other.y,
-          z: self.z $op_symbol other.z,
+    // Returns true if this point lies inside the axis-aligned box
+    // spanned by `min` and `max`. The bounds are inclusive, so a
+    // point sitting exactly on a face of the box counts as inside.
+    pub fn is_within_bounds(&self, min: &Vec3, max: &Vec3) -> bool {
+        return self.x >= min.x
+            && self.x <= max.x
+            && self.y >= min.y
+            && self.y <= max.y
+            && self.z >= min.z
+            && self.z <= max.z;
+    }
+
+    // Returns the point on the line segment from `a` to `b` that is
+    // closest to `point`. If `a` and `b` are the same point then the
+    // segment is degenerate and `a` is returned.
+    pub fn closest_point_on_segment(point: &Vec3, a: &Vec3, b: &Vec3) -> Vec3 {
+        return Vec3::project_param_on_segment(point, a, b).0;
+    }
+
+    // Like `closest_point_on_segment`, but also returns the parameter
+    // `t` in [0, 1] of the closest point, so that the point is equal
+    // to `a + (b - a) * t`. A degenerate segment returns `(a, 0.0)`.
+    pub fn project_param_on_segment(point: &Vec3, a: &Vec3, b: &Vec3) -> (Vec3, f32) {
+        let ab = b - a;
+        let length_squared = ab.length_squared();
+        if length_squared == 0.0 {
+            return (*a, 0.0);
         }
-      }
+
+        let t = (Vec3::dot(&(point - a), &ab) / length_squared).clamp(0.0, 1.0);
+        return (a + ab * t, t);
     }
 
-    // Implement a + b for the cases...
-    //
-    //   a: $VectorType,  b: &$VectorType
-    //   a: &$VectorType, b: $VectorType
-    //   a: $VectorType, b: $VectorType
-    //
-    // In each case we forward through to the implementation above.
-    impl $Operation<$VectorType> for $VectorType {
-      type Output = $VectorType;
+    // Returns the point on the ray starting at `origin` and heading
+    // along `direction` that is closest to `point`. Points that lie
+    // behind the ray's origin return `origin` itself. `direction` is
+    // assumed to be normalized.
+    pub fn closest_point_on_ray(point: &Vec3, origin: &Vec3, direction: &Vec3) -> Vec3 {
+        let t = Vec3::dot(&(point - origin), direction).max(0.0);
+        return origin + direction * t;
+    }
 
-      #[inline]
-      fn $op_fn(self, other: $VectorType) -> $VectorType {
-        &self $op_symbol &other
-      }
+    // Reflects this vector about the plane with the given normal,
+    // e.g. an incoming ray bouncing off a surface. `normal` is
+    // assumed to be normalized.
+    pub fn reflect(&self, normal: &Vec3) -> Vec3 {
+        return self - normal * (2.0 * Vec3::dot(self, normal));
     }
 
-    impl<'a> $Operation<&'a $VectorType> for $VectorType {
-      type Output = $VectorType;
+    // In-place version of `reflect`.
+    pub fn reflect_assign(&mut self, normal: &Vec3) {
+        *self = self.reflect(normal);
+    }
 
-      #[inline]
-      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
-        &self $op_symbol other
-      }
+    // The same as `reflect`, for two-sided surfaces. No `faceforward`
+    // step is needed: `reflect` already gives the same result for
+    // `normal` and `-normal`, so it doesn't matter which side of the
+    // surface the normal points out of.
+    pub fn reflect_oriented(&self, normal: &Vec3) -> Vec3 {
+        return self.reflect(normal);
     }
 
-    impl<'a> $Operation<$VectorType> for &'a $VectorType {
-      type Output = $VectorType;
+    // Returns the perpendicular distance from `point` to the infinite
+    // line through `line_point` along `line_dir`. `line_dir` is
+    // assumed to be normalized.
+    pub fn distance_to_line(point: &Vec3, line_point: &Vec3, line_dir: &Vec3) -> f32 {
+        return Vec3::cross(&(point - line_point), line_dir).length();
+    }
 
-      #[inline]
-      fn $op_fn(self, other: $VectorType) -> $VectorType {
-        self $op_symbol &other
-      }
+    // Returns the signed distance from `point` to the plane through
+    // `plane_point` with normal `plane_normal`. The result is positive
+    // when the point is on the side the normal points towards, and
+    // negative when it's behind the plane. `plane_normal` is assumed
+    // to be normalized.
+    pub fn signed_distance_to_plane(point: &Vec3, plane_point: &Vec3, plane_normal: &Vec3) -> f32 {
+        return Vec3::dot(&(point - plane_point), plane_normal);
     }
 
-    // Implement a + b where a is type &$VectorType and b is type f32
-    impl<'a> $Operation<f32> for &'a $VectorType {
-      type Output = $VectorType;
+    // Returns `self * factor + addend`, computed per component with
+    // a fused multiply-add. This rounds once instead of twice so it's
+    // more precise than doing the multiply and add separately.
+    pub fn mul_add(&self, factor: f32, addend: &Vec3) -> Vec3 {
+        return Vec3 {
+            x: self.x.mul_add(factor, addend.x),
+            y: self.y.mul_add(factor, addend.y),
+            z: self.z.mul_add(factor, addend.z),
+        };
+    }
 
-      fn $op_fn(self, other: f32) -> $VectorType {
-        $VectorType {
-          x: self.x $op_symbol other,
-          y: self.y $op_symbol other,
-          z: self.z $op_symbol other
-        }
-      }
+    // Returns the sum of `dot(a[i], b[i])` over every pair of vectors
+    // in the two slices. This is the batch version of `dot`. Panics
+    // if the slices have different lengths.
+    pub fn dot_slices(a: &[Vec3], b: &[Vec3]) -> f32 {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "dot_slices requires slices of equal length"
+        );
+        return a.iter().zip(b).map(|(a, b)| Vec3::dot(a, b)).sum();
     }
 
-    // Implement a + b where...
-    //
-    // a is $VectorType and b is f32
-    // a is f32 and b is $VectorType
-    // a is f32 and b is &$VectorType
-    //
-    // In each case we forward the logic to the implementation
-    // above.
-    impl $Operation<f32> for $VectorType {
-      type Output = $VectorType;
+    // Returns the projection of this vector onto `other`, i.e. the
+    // component of `self` that points along `other`.
+    pub fn project_onto(&self, other: &Vec3) -> Vec3 {
+        return other * (Vec3::dot(self, other) / other.length_squared());
+    }
 
-      #[inline]
-      fn $op_fn(self, other: f32) -> $VectorType {
-        &self $op_symbol other
-      }
+    // In-place version of `project_onto`.
+    pub fn project_onto_assign(&mut self, other: &Vec3) {
+        *self = self.project_onto(other);
     }
 
-    impl $Operation<$VectorType> for f32 {
-      type Output = $VectorType;
+    // Returns this vector with its component along `reference`
+    // removed, so the result is perpendicular to `reference`. This
+    // is a single Gram-Schmidt step.
+    pub fn orthogonalize(&self, reference: &Vec3) -> Vec3 {
+        return self - self.project_onto(reference);
+    }
 
-      #[inline]
-      fn $op_fn(self, other: $VectorType) -> $VectorType {
-        &other $op_symbol self
-      }
+    // Returns whichever component has the greatest absolute value,
+    // keeping its sign. For example `(1, -5, 3)` returns `-5`.
+    pub fn signed_max_abs_component(&self) -> f32 {
+        let mut result = self.x;
+        if self.y.abs() > result.abs() {
+            result = self.y;
+        }
+        if self.z.abs() > result.abs() {
+            result = self.z;
+        }
+        return result;
     }
 
-    impl<'a> $Operation<&'a $VectorType> for f32 {
-      type Output = $VectorType;
+    // Returns the angle in radians between this vector and `other`.
+    // Both vectors are assumed to be normalized.
+    fn angle_between_unit(&self, other: &Vec3) -> f32 {
+        return Vec3::dot(self, other).clamp(-1.0, 1.0).acos();
+    }
 
-      #[inline]
-      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
-        other $op_symbol self
-      }
+    // Returns some unit vector perpendicular to this (non-zero) vector.
+    fn any_perpendicular(&self) -> Vec3 {
+        // Pick whichever basis vector is least aligned with this one to
+        // build a perpendicular axis from.
+        let helper = if self.x.abs() < 0.9 {
+            Vec3::new(1.0, 0.0, 0.0)
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        };
+        return Vec3::cross(self, &helper).normalize();
     }
-  };
-}
 
-// It also implements unary operators like - a where a is of
-// type Vec3 or &Vec3.
-macro_rules! impl_unary_operations {
-  // $VectorType is something like `Vec3`
-  // $Operation is something like `Neg`
-  // $op_fn is something like `neg`
-  // $op_symbol is something like `-`
-  ($VectorType:ident $Operation:ident $op_fn:ident $op_symbol:tt) => {
+    // Spherically interpolates between the unit vectors `a` and `b`
+    // along the shorter arc, moving at a constant angular speed as
+    // `t` goes from 0 to 1. When `a` and `b` point in exactly opposite
+    // directions every arc is equally short, and an arbitrary one is
+    // used.
+    pub fn slerp(a: &Vec3, b: &Vec3, t: f32) -> Vec3 {
+        let angle = a.angle_between_unit(b);
+        let sin_angle = angle.sin();
+        if sin_angle.abs() < 0.00001 {
+            if Vec3::dot(a, b) > 0.0 {
+                // The vectors are (nearly) parallel, so a linear blend
+                // is indistinguishable from the arc.
+                return (a + (b - a) * t).normalize();
+            }
 
-    // Implement the unary operator for references
-    impl<'a> $Operation for &'a $VectorType {
-      type Output = $VectorType;
+            // The vectors are (nearly) opposite. Rotate `a` through
+            // `b`'s perpendicular component if it has one, and about
+            // an arbitrary perpendicular axis otherwise.
+            let towards = (b - a * Vec3::dot(a, b)).normalize_or(a.any_perpendicular());
+            return a * (t * angle).cos() + towards * (t * angle).sin();
+        }
 
-      fn $op_fn(self) -> Vec3 {
-        $VectorType {
-          x: $op_symbol self.x,
-          y: $op_symbol self.y,
-          z: $op_symbol self.z,
+        let a_weight = ((1.0 - t) * angle).sin() / sin_angle;
+        let b_weight = (t * angle).sin() / sin_angle;
+        return a * a_weight + b * b_weight;
+    }
+
+    // Rotates this direction towards `target` by at most
+    // `max_angle_rad` radians. Returns `target` if it's already
+    // within that angle. Both vectors are assumed to be normalized.
+    pub fn rotate_towards(&self, target: &Vec3, max_angle_rad: f32) -> Vec3 {
+        let angle = self.angle_between_unit(target);
+        if angle <= max_angle_rad {
+            return *target;
         }
-      }
+        return Vec3::slerp(self, target, max_angle_rad / angle);
     }
 
-    // Have the operator on values forward through to the implementation
-    // above
-    impl $Operation for $VectorType {
-      type Output = $VectorType;
+    // Reflects this velocity about the surface with the given normal,
+    // scaling the component along the normal by `restitution`. A
+    // restitution of 1.0 is a perfect bounce (the same as `reflect`)
+    // and 0.0 removes the normal component entirely. `normal` is
+    // assumed to be normalized.
+    pub fn bounce(&self, normal: &Vec3, restitution: f32) -> Vec3 {
+        return self.collide(normal, restitution, 0.0);
+    }
 
-      #[inline]
-      fn $op_fn(self) -> Vec3 {
-        $op_symbol &self
-      }
+    // Like `bounce`, but also applies friction along the surface:
+    // the tangential part of the velocity is scaled by
+    // `1.0 - friction`, so a friction of 0.0 keeps it as is and 1.0
+    // removes it. `normal` is assumed to be normalized.
+    pub fn collide(&self, normal: &Vec3, restitution: f32, friction: f32) -> Vec3 {
+        let normal_part = normal * Vec3::dot(self, normal);
+        let tangent_part = self - normal_part;
+        return tangent_part * (1.0 - friction) - normal_part * restitution;
     }
-  };
-}
 
-// Implement add-assignment operators like a += b where a and
-// b is either &Vec3 or Vec3 (in this case a is always of type
-// &mut Vec3).
-macro_rules! impl_op_assign {
-  // $VectorType is something like `Vec3`
-  // $OperationAssign is something like `AddAssign`
-  // $op_fn is something like `add_assign`
-  // $op_symbol is something like `+=`
-  ($VectorType:ident $OperationAssign:ident $op_fn:ident $op_symbol:tt) => {
-    // Implement $OperationAssign for RHS &Vec3
-    impl<'a> $OperationAssign<&'a $VectorType> for $VectorType {
-      fn $op_fn(&mut self, other: &'a $VectorType) {
-        *self = $VectorType {
-          x: self.x $op_symbol other.x,
-          y: self.y $op_symbol other.y,
-          z: self.z $op_symbol other.z,
+    // Builds a mask vector holding 1.0 in each component where
+    // `compare` holds between the components of `self` and `other`,
+    // and 0.0 everywhere else.
+    fn compare_mask(&self, other: &Vec3, compare: fn(f32, f32) -> bool) -> Vec3 {
+        let mask = |a: f32, b: f32| if compare(a, b) { 1.0 } else { 0.0 };
+        return Vec3 {
+            x: mask(self.x, other.x),
+            y: mask(self.y, other.y),
+            z: mask(self.z, other.z),
         };
-      }
     }
 
-    // Implement $OperationAssign for RHS Vec3 by forwarding through to the
-    // implementation above
-    impl $OperationAssign for $VectorType {
-      #[inline]
-      fn $op_fn(&mut self, other: $VectorType) {
-        *self = *self $op_symbol &other
-      }
+    // Component-wise comparisons returning a mask vector with 1.0
+    // where the comparison holds and 0.0 where it doesn't. These
+    // are meant to be used with `Vec3::select`.
+    pub fn cmp_lt(&self, other: &Vec3) -> Vec3 {
+        return self.compare_mask(other, |a, b| a < b);
     }
-  };
-}
 
-impl_binary_operations!(Vec3 Add add +);
-impl_op_assign!(Vec3 AddAssign add_assign +);
+    pub fn cmp_le(&self, other: &Vec3) -> Vec3 {
+        return self.compare_mask(other, |a, b| a <= b);
+    }
 
-impl_binary_operations!(Vec3 Sub sub -);
-impl_op_assign!(Vec3 SubAssign sub_assign -);
-impl_unary_operations!(Vec3 Neg neg -);
+    pub fn cmp_gt(&self, other: &Vec3) -> Vec3 {
+        return self.compare_mask(other, |a, b| a > b);
+    }
 
-impl_binary_operations!(Vec3 Mul mul *);
-impl_op_assign!(Vec3 MulAssign mul_assign *);
+    pub fn cmp_ge(&self, other: &Vec3) -> Vec3 {
+        return self.compare_mask(other, |a, b| a >= b);
+    }
 
-impl_binary_operations!(Vec3 Div div /);
-impl_op_assign!(Vec3 DivAssign div_assign /);
+    // Returns a vector taking each component from `a` where `mask`
+    // is non-zero and from `b` where it's zero.
+    pub fn select(mask: &Vec3, a: &Vec3, b: &Vec3) -> Vec3 {
+        let pick = |m: f32, a: f32, b: f32| if m != 0.0 { a } else { b };
+        return Vec3 {
+            x: pick(mask.x, a.x, b.x),
+            y: pick(mask.y, a.y, b.y),
+            z: pick(mask.z, a.z, b.z),
+        };
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Returns the component-wise (Hadamard) product of the two
+    // vectors. This is exactly what `self * other` does, since the
+    // `*` operator on Vec3 multiplies component by component rather
+    // than computing a dot or cross product. See `dot` and `cross`
+    // for those.
+    pub fn hadamard(&self, other: &Vec3) -> Vec3 {
+        return self * other;
+    }
 
-    #[test]
-    fn add() {
-        let a = Vec3::new(0.0, 1.0, 2.0);
-        let b = Vec3::new(3.0, 4.0, 5.0);
-        assert_eq!(&a + &b, Vec3::new(3.0, 5.0, 7.0));
-        assert_eq!(a + &b, Vec3::new(3.0, 5.0, 7.0));
-        assert_eq!(&a + b, Vec3::new(3.0, 5.0, 7.0));
-        assert_eq!(a + b, Vec3::new(3.0, 5.0, 7.0));
+    // Returns each component squared, i.e. `self * self`.
+    pub fn square(&self) -> Vec3 {
+        return self * self;
+    }
 
-        // Test for RHS value type
-        {
-            let mut c = Vec3::ONE;
-            c += a;
-            assert_eq!(c, Vec3::new(1.0, 2.0, 3.0));
-        }
+    // Returns each component cubed, i.e. `self * self * self`.
+    pub fn cube(&self) -> Vec3 {
+        return self * self * self;
+    }
 
-        // Test for RHS borrowed reference
-        {
-            let mut c = Vec3::ONE;
-            c += &a;
-            assert_eq!(c, Vec3::new(1.0, 2.0, 3.0));
-        }
+    // Returns the reciprocal (1 / component) of each component, but
+    // with 0.0 in place of infinity for components that are zero.
+    // Some physics integrators rely on this to mask out fixed axes.
+    pub fn recip_or_zero(&self) -> Vec3 {
+        let recip = |value: f32| if value == 0.0 { 0.0 } else { 1.0 / value };
+        return Vec3 {
+            x: recip(self.x),
+            y: recip(self.y),
+            z: recip(self.z),
+        };
     }
 
-    #[test]
-    fn subtract() {
-        let a = Vec3::new(0.0, 1.0, 2.0);
-        let b = Vec3::new(3.0, 4.0, 5.0);
-        assert_eq!(&a - &b, Vec3::new(-3.0, -3.0, -3.0));
-        assert_eq!(a - &b, Vec3::new(-3.0, -3.0, -3.0));
-        assert_eq!(&a - b, Vec3::new(-3.0, -3.0, -3.0));
-        assert_eq!(a - b, Vec3::new(-3.0, -3.0, -3.0));
+    // Returns the signed angle in radians, in (-π, π], that rotates
+    // `a` onto `b` about `axis`. The angle is positive when the
+    // rotation is counter-clockwise looking down `axis` (right hand
+    // rule), e.g. from X towards Y about Z.
+    pub fn signed_angle(a: &Vec3, b: &Vec3, axis: &Vec3) -> f32 {
+        let sin_part = Vec3::dot(&Vec3::cross(a, b), axis);
+        let cos_part = Vec3::dot(a, b);
+        return sin_part.atan2(cos_part);
+    }
 
-        // Test for RHS value type
-        {
-            let mut c = Vec3::ONE;
-            c -= a;
-            assert_eq!(c, Vec3::new(1.0, 0.0, -1.0));
-        }
+    // Builds an RGB color from hue, saturation and value. `h` is in
+    // degrees in [0, 360) (values outside that range wrap around),
+    // `s` and `v` are in [0, 1]. The resulting r, g and b are stored
+    // in x, y and z respectively, each in [0, 1].
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Vec3 {
+        let chroma = v * s;
+        let sector = h.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = v - chroma;
+        return Vec3::new(r + m, g + m, b + m);
+    }
 
-        // Test for RHS borrowed reference
-        {
-            let mut c = Vec3::ONE;
-            c -= &a;
-            assert_eq!(c, Vec3::new(1.0, 0.0, -1.0));
-        }
+    // Treats this vector as an RGB color and returns it as
+    // `(hue, saturation, value)`, the inverse of `from_hsv`. Hue is
+    // in degrees in [0, 360) and is 0.0 for greys.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let max = self.x.max(self.y).max(self.z);
+        let min = self.x.min(self.y).min(self.z);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == self.x {
+            60.0 * ((self.y - self.z) / delta).rem_euclid(6.0)
+        } else if max == self.y {
+            60.0 * ((self.z - self.x) / delta + 2.0)
+        } else {
+            60.0 * ((self.x - self.y) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        return (hue, saturation, max);
     }
 
-    #[test]
-    fn multiply() {
-        let a = Vec3::new(0.0, 1.0, 2.0);
-        let b = Vec3::new(3.0, 4.0, 5.0);
-        assert_eq!(&a * &b, Vec3::new(0.0, 4.0, 10.0));
-        assert_eq!(a * &b, Vec3::new(0.0, 4.0, 10.0));
-        assert_eq!(&a * b, Vec3::new(0.0, 4.0, 10.0));
-        assert_eq!(a * b, Vec3::new(0.0, 4.0, 10.0));
+    // Returns the components sorted in ascending order. NaN
+    // components are sorted after every other value.
+    pub fn sorted_components(&self) -> [f32; 3] {
+        let mut components = [self.x, self.y, self.z];
+        components.sort_by(|a, b| {
+            a.partial_cmp(b)
+                .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+        });
+        return components;
+    }
 
-        // Test for RHS value type
-        {
-            let mut c = Vec3::from_float(2.0);
-            c *= a;
-            assert_eq!(c, 2.0 * a);
-        }
+    // Returns the middle value of the three components, using the
+    // same NaN-last ordering as `sorted_components`.
+    pub fn median_component(&self) -> f32 {
+        return self.sorted_components()[1];
+    }
 
-        // Test for RHS borrowed reference
+    // Returns whichever of ±X, ±Y or ±Z is closest in direction to
+    // this vector, i.e. the unit axis along this vector's largest
+    // magnitude component, keeping that component's sign.
+    pub fn snap_to_nearest_axis(&self) -> Vec3 {
+        let mut axis = Axis::X;
+        if self.y.abs() > self.component(axis).abs() {
+            axis = Axis::Y;
+        }
+        if self.z.abs() > self.component(axis).abs() {
+            axis = Axis::Z;
+        }
+        let sign = if self.component(axis) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        return Vec3::from(axis) * sign;
+    }
+
+    // Mirrors this vector across the line spanned by `dir`, i.e.
+    // rotates it 180 degrees about `dir`. Note that this is different
+    // from `reflect`, which mirrors across the plane whose *normal*
+    // is the given vector. For a unit `dir` the two are negations of
+    // each other.
+    pub fn reflect_about_direction(&self, dir: &Vec3) -> Vec3 {
+        return self.project_onto(dir) * 2.0 - self;
+    }
+
+    // Linearly interpolates from `a` to `b`, returning `a` when `t` is
+    // 0.0 and `b` when `t` is 1.0. `t` isn't clamped, so values
+    // outside [0, 1] extrapolate along the line through `a` and `b`.
+    // `a` and `b` can be passed either by value or by reference.
+    pub fn lerp<A: Borrow<Vec3>, B: Borrow<Vec3>>(a: A, b: B, t: f32) -> Vec3 {
+        let a: &Vec3 = a.borrow();
+        let b: &Vec3 = b.borrow();
+        return a + (b - a) * t;
+    }
+
+    // The inverse of `lerp`: returns the per-component `t` for which
+    // `lerp(a, b, t)` gives `value`. Axes where `a` and `b` are equal
+    // have no well-defined `t` and return 0.0.
+    pub fn inverse_lerp(a: &Vec3, b: &Vec3, value: &Vec3) -> Vec3 {
+        let inverse = |a: f32, b: f32, value: f32| {
+            let range = b - a;
+            if range == 0.0 {
+                0.0
+            } else {
+                (value - a) / range
+            }
+        };
+        return Vec3 {
+            x: inverse(a.x, b.x, value.x),
+            y: inverse(a.y, b.y, value.y),
+            z: inverse(a.z, b.z, value.z),
+        };
+    }
+
+    // Linearly maps each component from the range [in_min, in_max]
+    // to the range [out_min, out_max]. Axes with an empty input range
+    // map to `out_min`.
+    pub fn remap(&self, in_min: &Vec3, in_max: &Vec3, out_min: &Vec3, out_max: &Vec3) -> Vec3 {
+        let t = Vec3::inverse_lerp(in_min, in_max, self);
+        return out_min + (out_max - out_min) * t;
+    }
+
+    // Replaces non-finite components: NaN becomes `nan`, positive
+    // infinity becomes `pos_inf` and negative infinity becomes
+    // `neg_inf`. Finite components are left as they are.
+    pub fn nan_to_num(&self, nan: f32, pos_inf: f32, neg_inf: f32) -> Vec3 {
+        let sanitize = |value: f32| {
+            if value.is_nan() {
+                nan
+            } else if value == f32::INFINITY {
+                pos_inf
+            } else if value == f32::NEG_INFINITY {
+                neg_inf
+            } else {
+                value
+            }
+        };
+        return Vec3 {
+            x: sanitize(self.x),
+            y: sanitize(self.y),
+            z: sanitize(self.z),
+        };
+    }
+
+    // Blends the three triangle vertices (or per-vertex attributes)
+    // `a`, `b` and `c` using barycentric weights `(u, v, w)`, returning
+    // `u * a + v * b + w * c`. The weights normally sum to 1.
+    pub fn interpolate_triangle(a: &Vec3, b: &Vec3, c: &Vec3, bary: (f32, f32, f32)) -> Vec3 {
+        let (u, v, w) = bary;
+        return a * u + b * v + c * w;
+    }
+
+    // Returns true if every component is exactly zero.
+    pub fn is_zero(&self) -> bool {
+        return self.x == 0.0 && self.y == 0.0 && self.z == 0.0;
+    }
+
+    // Returns true if this vector's length is less than `epsilon`.
+    pub fn is_approximately_zero(&self, epsilon: f32) -> bool {
+        return self.length_squared() < epsilon * epsilon;
+    }
+
+    // Packs the sign bits of the components into the low bits of a
+    // u8: bit 0 is set if `x` is negative, bit 1 for `y` and bit 2
+    // for `z`. Negative zero counts as negative. This is handy for
+    // picking a traversal order when casting rays through a BVH.
+    pub fn sign_bits(&self) -> u8 {
+        return (self.x.is_sign_negative() as u8)
+            | (self.y.is_sign_negative() as u8) << 1
+            | (self.z.is_sign_negative() as u8) << 2;
+    }
+
+    // Returns the heading of this vector in the XZ plane, in radians
+    // from +X. This is the inverse of `from_angle_xz`; the `y`
+    // component is ignored.
+    pub fn heading_xz(&self) -> f32 {
+        return self.z.atan2(self.x);
+    }
+
+    // Returns the coordinates of this vector in the basis `u`, `v`,
+    // `w`, i.e. `(dot(self, u), dot(self, v), dot(self, w))`. The
+    // basis is assumed to be orthonormal.
+    pub fn coordinates_in_basis(&self, u: &Vec3, v: &Vec3, w: &Vec3) -> Vec3 {
+        return Vec3 {
+            x: Vec3::dot(self, u),
+            y: Vec3::dot(self, v),
+            z: Vec3::dot(self, w),
+        };
+    }
+
+    // Clamps each component to [-limit, limit], using the matching
+    // component of `limits` as the limit for each axis. Panics if any
+    // limit is negative or NaN, as `f32::clamp` does.
+    pub fn clamp_abs(&self, limits: &Vec3) -> Vec3 {
+        return Vec3 {
+            x: self.x.clamp(-limits.x, limits.x),
+            y: self.y.clamp(-limits.y, limits.y),
+            z: self.z.clamp(-limits.z, limits.z),
+        };
+    }
+
+    // Returns the point on the surface of the sphere at `center` with
+    // the given `radius` that is closest to this point. Works for
+    // points both inside and outside the sphere. If this point is
+    // exactly at `center` every surface point is equally close, and
+    // the point along +X from `center` is returned.
+    pub fn project_to_sphere(&self, center: &Vec3, radius: f32) -> Vec3 {
+        let offset = self - center;
+        let length = offset.length();
+        if length == 0.0 {
+            return center + Vec3::new(radius, 0.0, 0.0);
+        }
+        return center + offset * (radius / length);
+    }
+
+    // Returns the winding direction of the triangle `a`, `b`, `c` as
+    // seen looking down `axis`: 1.0 if it winds counter-clockwise,
+    // -1.0 if clockwise, and 0.0 if the points are collinear or the
+    // triangle is edge-on to `axis`.
+    pub fn winding_sign(a: &Vec3, b: &Vec3, c: &Vec3, axis: &Vec3) -> f32 {
+        let orientation = Vec3::dot(&Vec3::cross(&(b - a), &(c - a)), axis);
+        if orientation > 0.0 {
+            return 1.0;
+        } else if orientation < 0.0 {
+            return -1.0;
+        }
+        return 0.0;
+    }
+
+    // Returns the point on the triangle `a`, `b`, `c` (including its
+    // interior) that is closest to `p`. This works out which vertex,
+    // edge or face region `p` projects into and handles each one, as
+    // described in Ericson's "Real-Time Collision Detection".
+    pub fn closest_point_on_triangle(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3) -> Vec3 {
+        let ab = b - a;
+        let ac = c - a;
+
+        // Vertex region outside a
+        let ap = p - a;
+        let d1 = Vec3::dot(&ab, &ap);
+        let d2 = Vec3::dot(&ac, &ap);
+        if d1 <= 0.0 && d2 <= 0.0 {
+            return *a;
+        }
+
+        // Vertex region outside b
+        let bp = p - b;
+        let d3 = Vec3::dot(&ab, &bp);
+        let d4 = Vec3::dot(&ac, &bp);
+        if d3 >= 0.0 && d4 <= d3 {
+            return *b;
+        }
+
+        // Edge region of ab
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+            let v = d1 / (d1 - d3);
+            return a + ab * v;
+        }
+
+        // Vertex region outside c
+        let cp = p - c;
+        let d5 = Vec3::dot(&ab, &cp);
+        let d6 = Vec3::dot(&ac, &cp);
+        if d6 >= 0.0 && d5 <= d6 {
+            return *c;
+        }
+
+        // Edge region of ac
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+            let w = d2 / (d2 - d6);
+            return a + ac * w;
+        }
+
+        // Edge region of bc
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            return b + (c - b) * w;
+        }
+
+        // Face region, so p projects inside the triangle
+        let denom = 1.0 / (va + vb + vc);
+        let v = vb * denom;
+        let w = vc * denom;
+        return a + ab * v + ac * w;
+    }
+
+    // Like `slerp`, but travels along the longer of the two arcs
+    // between the unit vectors `a` and `b` (the one that sweeps more
+    // than π radians). The long arc is undefined when `a` and `b` are
+    // parallel or exactly opposite, and this falls back to `slerp`.
+    pub fn slerp_long(a: &Vec3, b: &Vec3, t: f32) -> Vec3 {
+        let short_angle = a.angle_between_unit(b);
+        let sin_angle = short_angle.sin();
+        if sin_angle.abs() < 0.00001 {
+            return Vec3::slerp(a, b, t);
+        }
+
+        // Going the long way round is the same as going the short way
+        // with an angle of `short_angle - 2π`.
+        let angle = short_angle - 2.0 * std::f32::consts::PI;
+        let a_weight = ((1.0 - t) * angle).sin() / sin_angle;
+        let b_weight = (t * angle).sin() / sin_angle;
+        return a * a_weight + b * b_weight;
+    }
+
+    // Adds `other * scale` to this vector in place, using a fused
+    // multiply-add per component. This is the `pos += vel * dt` step
+    // of an integrator without the temporary vector.
+    pub fn add_scaled_assign(&mut self, other: &Vec3, scale: f32) {
+        *self = other.mul_add(scale, self);
+    }
+
+    // Scales this vector up to length `min` if it's shorter than
+    // that, keeping its direction. Longer vectors are returned
+    // unchanged. The zero vector has no direction, so it's also
+    // returned unchanged.
+    pub fn clamp_length_min(self, min: f32) -> Vec3 {
+        let length = self.length();
+        if length == 0.0 || length >= min {
+            return self;
+        }
+        return self * (min / length);
+    }
+
+    // Returns the arithmetic mean of the components, `(x + y + z) / 3`.
+    pub fn mean(&self) -> f32 {
+        return (self.x + self.y + self.z) / 3.0;
+    }
+
+    // Returns the geometric mean of the components, the cube root of
+    // `x * y * z`. This is only really meaningful for positive
+    // components. If any component is zero the result is 0.0, and if
+    // the product is negative the result is its (negative) real cube
+    // root rather than NaN. Each component's root is taken separately
+    // so the product can't overflow or underflow.
+    pub fn geometric_mean(&self) -> f32 {
+        return self.x.cbrt() * self.y.cbrt() * self.z.cbrt();
+    }
+
+    // Returns a new vector with `f` applied to each component.
+    fn map<F: Fn(f32) -> f32>(&self, f: F) -> Vec3 {
+        return Vec3 {
+            x: f(self.x),
+            y: f(self.y),
+            z: f(self.z),
+        };
+    }
+
+    // Converts each component from radians to degrees, e.g. for a
+    // set of Euler angles.
+    pub fn to_degrees(&self) -> Vec3 {
+        return self.map(f32::to_degrees);
+    }
+
+    // Converts each component from degrees to radians.
+    pub fn to_radians(&self) -> Vec3 {
+        return self.map(f32::to_radians);
+    }
+
+    // Component-wise trigonometric functions, treating each
+    // component as an angle in radians.
+    pub fn sin(&self) -> Vec3 {
+        return self.map(f32::sin);
+    }
+
+    pub fn cos(&self) -> Vec3 {
+        return self.map(f32::cos);
+    }
+
+    pub fn tan(&self) -> Vec3 {
+        return self.map(f32::tan);
+    }
+
+    // Returns `(self.sin(), self.cos())` in one call.
+    pub fn sin_cos(&self) -> (Vec3, Vec3) {
+        return (self.sin(), self.cos());
+    }
+
+    // Samples the piecewise-linear path through `points`, where `t`
+    // is the fraction of the path's total length travelled from the
+    // first point (0.0) to the last (1.0). `t` is clamped to [0, 1].
+    // A path with a single point always returns that point, and an
+    // empty path returns `Vec3::ZERO`.
+    pub fn sample_path(points: &[Vec3], t: f32) -> Vec3 {
+        if points.is_empty() {
+            return Vec3::ZERO;
+        }
+
+        let total_length: f32 = points.windows(2).map(|w| (w[1] - w[0]).length()).sum();
+        let mut remaining = t.clamp(0.0, 1.0) * total_length;
+        for segment in points.windows(2) {
+            let segment_length = (segment[1] - segment[0]).length();
+            if remaining <= segment_length && segment_length > 0.0 {
+                return Vec3::lerp(segment[0], segment[1], remaining / segment_length);
+            }
+            remaining -= segment_length;
+        }
+        return points[points.len() - 1];
+    }
+
+    // Evaluates the uniform Catmull-Rom spline segment between `p1`
+    // and `p2`, using `p0` and `p3` as the neighbouring control
+    // points. Returns `p1` at `t = 0` and `p2` at `t = 1`. Consecutive
+    // segments sharing control points join with matching tangents.
+    pub fn catmull_rom(p0: &Vec3, p1: &Vec3, p2: &Vec3, p3: &Vec3, t: f32) -> Vec3 {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let a = p1 * 2.0;
+        let b = p2 - p0;
+        let c = p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3;
+        let d = p1 * 3.0 - p0 - p2 * 3.0 + p3;
+        return (a + b * t + c * t2 + d * t3) * 0.5;
+    }
+
+    // Evaluates the cubic Bézier curve with control points `p0` to
+    // `p3` at `t`, using De Casteljau's algorithm (repeated lerps).
+    // Returns `p0` at `t = 0` and `p3` at `t = 1`.
+    pub fn bezier_cubic(p0: &Vec3, p1: &Vec3, p2: &Vec3, p3: &Vec3, t: f32) -> Vec3 {
+        let a = Vec3::lerp(p0, p1, t);
+        let b = Vec3::lerp(p1, p2, t);
+        let c = Vec3::lerp(p2, p3, t);
+        let d = Vec3::lerp(a, b, t);
+        let e = Vec3::lerp(b, c, t);
+        return Vec3::lerp(d, e, t);
+    }
+
+    // Hashes the bit patterns of the components into a deterministic
+    // pseudo-random value in [0, 1). The same vector always gives the
+    // same value, which is handy for things like jittering grid
+    // positions without carrying an RNG around. This is a cheap hash,
+    // not statistically rigorous noise. Note that 0.0 and -0.0 have
+    // different bit patterns and so hash differently.
+    pub fn hash_to_unit(&self) -> f32 {
+        let mut h = self.x.to_bits().wrapping_mul(0x8da6_b343)
+            ^ self.y.to_bits().wrapping_mul(0xd816_3841)
+            ^ self.z.to_bits().wrapping_mul(0xcb1a_b31f);
+
+        // Murmur3's finalizer, to spread every input bit across the
+        // whole output.
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85eb_ca6b);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xc2b2_ae35);
+        h ^= h >> 16;
+
+        // Keep the top 24 bits, which is all the precision an f32 in
+        // [0, 1) can hold.
+        return (h >> 8) as f32 / (1u32 << 24) as f32;
+    }
+
+    // Returns a copy of this vector with only the component along
+    // `axis` multiplied by `factor`.
+    pub fn scale_axis(&self, axis: Axis, factor: f32) -> Vec3 {
+        let mut result = *self;
+        result.set_component(axis, self.component(axis) * factor);
+        return result;
+    }
+
+    // Quantizes each component to an i16 by mapping the range
+    // [min, max] linearly onto [i16::MIN, i16::MAX]. Components outside
+    // the range are clamped to it. Use `dequantize` with the same
+    // bounds to get an approximation of the original vector back.
+    pub fn quantize(&self, min: &Vec3, max: &Vec3) -> [i16; 3] {
+        let t = Vec3::inverse_lerp(min, max, self);
+        let quantize = |t: f32| ((t.clamp(0.0, 1.0) * 65535.0).round() - 32768.0) as i16;
+        return [quantize(t.x), quantize(t.y), quantize(t.z)];
+    }
+
+    // The inverse of `quantize`. The result is within one step of
+    // `(max - min) / 65535` of the original on each axis.
+    pub fn dequantize(q: [i16; 3], min: &Vec3, max: &Vec3) -> Vec3 {
+        let t = |q: i16| (q as f32 + 32768.0) / 65535.0;
+        return min + (max - min) * Vec3::new(t(q[0]), t(q[1]), t(q[2]));
+    }
+
+    // Encodes this unit vector as a point in [-1, 1]² using the
+    // octahedral mapping, which is a compact way of storing normals
+    // (e.g. in a G-buffer). Decode with `decode_octahedral`.
+    pub fn encode_octahedral(&self) -> (f32, f32) {
+        let sign = |value: f32| if value >= 0.0 { 1.0 } else { -1.0 };
+        let n = self / (self.x.abs() + self.y.abs() + self.z.abs());
+        if n.z >= 0.0 {
+            return (n.x, n.y);
+        }
+
+        // Fold the lower hemisphere out over the corners of the square
+        return ((1.0 - n.y.abs()) * sign(n.x), (1.0 - n.x.abs()) * sign(n.y));
+    }
+
+    // Decodes a unit vector from the octahedral encoding produced by
+    // `encode_octahedral`.
+    pub fn decode_octahedral(u: f32, v: f32) -> Vec3 {
+        let mut n = Vec3::new(u, v, 1.0 - u.abs() - v.abs());
+
+        // Unfold the lower hemisphere
+        let t = (-n.z).max(0.0);
+        n.x += if n.x >= 0.0 { -t } else { t };
+        n.y += if n.y >= 0.0 { -t } else { t };
+        return n.normalize();
+    }
+
+    // Returns the `index`-th of `count` unit vectors spread roughly
+    // evenly over the sphere, using the Fibonacci spiral. Points go
+    // from near +Y (index 0) to near -Y (index count - 1). `index`
+    // should be less than `count`.
+    pub fn fibonacci_sphere(index: usize, count: usize) -> Vec3 {
+        let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+        let y = 1.0 - (2 * index + 1) as f32 / count as f32;
+        let radius = (1.0 - y * y).max(0.0).sqrt();
+        let theta = golden_angle * index as f32;
+        return Vec3::new(theta.cos() * radius, y, theta.sin() * radius);
+    }
+
+    // Returns true if `compare` holds for every pair of components.
+    fn all_components(&self, other: &Vec3, compare: fn(f32, f32) -> bool) -> bool {
+        return compare(self.x, other.x) && compare(self.y, other.y) && compare(self.z, other.z);
+    }
+
+    // Returns true if `compare` holds for at least one pair of
+    // components.
+    fn any_components(&self, other: &Vec3, compare: fn(f32, f32) -> bool) -> bool {
+        return compare(self.x, other.x) || compare(self.y, other.y) || compare(self.z, other.z);
+    }
+
+    // Component-wise comparisons reduced to a single bool. The
+    // `all_*` methods return true if the comparison holds on every
+    // axis and the `any_*` methods if it holds on at least one.
+    pub fn all_lt(&self, other: &Vec3) -> bool {
+        return self.all_components(other, |a, b| a < b);
+    }
+
+    pub fn all_le(&self, other: &Vec3) -> bool {
+        return self.all_components(other, |a, b| a <= b);
+    }
+
+    pub fn all_gt(&self, other: &Vec3) -> bool {
+        return self.all_components(other, |a, b| a > b);
+    }
+
+    pub fn all_ge(&self, other: &Vec3) -> bool {
+        return self.all_components(other, |a, b| a >= b);
+    }
+
+    pub fn any_lt(&self, other: &Vec3) -> bool {
+        return self.any_components(other, |a, b| a < b);
+    }
+
+    pub fn any_le(&self, other: &Vec3) -> bool {
+        return self.any_components(other, |a, b| a <= b);
+    }
+
+    pub fn any_gt(&self, other: &Vec3) -> bool {
+        return self.any_components(other, |a, b| a > b);
+    }
+
+    pub fn any_ge(&self, other: &Vec3) -> bool {
+        return self.any_components(other, |a, b| a >= b);
+    }
+
+    // Returns true if the axis-aligned boxes `[min_a, max_a]` and
+    // `[min_b, max_b]` intersect. Boxes that only touch along a face,
+    // edge or corner count as overlapping.
+    pub fn aabb_overlap(min_a: &Vec3, max_a: &Vec3, min_b: &Vec3, max_b: &Vec3) -> bool {
+        return min_a.all_le(max_b) && min_b.all_le(max_a);
+    }
+
+    // Intersects the ray from `origin` along `dir` with the sphere at
+    // `center` with the given `radius`. Returns the smallest
+    // non-negative `t` at which `origin + dir * t` lies on the sphere,
+    // or `None` if the ray misses it. If the ray starts inside the
+    // sphere this is where it exits. `dir` is assumed to be
+    // normalized.
+    pub fn ray_sphere_intersect(
+        origin: &Vec3,
+        dir: &Vec3,
+        center: &Vec3,
+        radius: f32,
+    ) -> Option<f32> {
+        let to_origin = origin - center;
+        let b = Vec3::dot(&to_origin, dir);
+        let c = to_origin.length_squared() - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let near = -b - root;
+        if near >= 0.0 {
+            return Some(near);
+        }
+        let far = -b + root;
+        if far >= 0.0 {
+            return Some(far);
+        }
+        return None;
+    }
+
+    // Intersects the ray from `origin` along `dir` with the plane
+    // through `plane_point` with normal `plane_normal`. Returns the
+    // `t` at which `origin + dir * t` lies on the plane, or `None` if
+    // the ray is parallel to the plane or the plane is behind it.
+    pub fn ray_plane_intersect(
+        origin: &Vec3,
+        dir: &Vec3,
+        plane_point: &Vec3,
+        plane_normal: &Vec3,
+    ) -> Option<f32> {
+        let denominator = Vec3::dot(dir, plane_normal);
+        if denominator.abs() < 0.000001 {
+            return None;
+        }
+
+        let t = Vec3::dot(&(plane_point - origin), plane_normal) / denominator;
+        if t < 0.0 {
+            return None;
+        }
+        return Some(t);
+    }
+
+    // Intersects the ray from `origin` along `dir` with the triangle
+    // `v0`, `v1`, `v2` using the Möller-Trumbore algorithm. On a hit
+    // returns `(t, u, v)` where the hit point is `origin + dir * t`
+    // and `(u, v)` are its barycentric coordinates, i.e. the point is
+    // also `v0 * (1 - u - v) + v1 * u + v2 * v`.
+    //
+    // Returns `None` if the ray misses, if the triangle is behind the
+    // ray, if the ray is parallel to the triangle, or if it hits the
+    // back face. The front face is the one from which `v0`, `v1`, `v2`
+    // appear counter-clockwise.
+    pub fn ray_triangle_intersect(
+        origin: &Vec3,
+        dir: &Vec3,
+        v0: &Vec3,
+        v1: &Vec3,
+        v2: &Vec3,
+    ) -> Option<(f32, f32, f32)> {
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        let p = Vec3::cross(dir, &edge2);
+        let determinant = Vec3::dot(&edge1, &p);
+        if determinant < 0.000001 {
+            return None;
+        }
+        let inverse_determinant = 1.0 / determinant;
+
+        let to_origin = origin - v0;
+        let u = Vec3::dot(&to_origin, &p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = Vec3::cross(&to_origin, &edge1);
+        let v = Vec3::dot(dir, &q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = Vec3::dot(&edge2, &q) * inverse_determinant;
+        if t < 0.0 {
+            return None;
+        }
+        return Some((t, u, v));
+    }
+
+    // Returns the root-mean-square of the components,
+    // `sqrt((x² + y² + z²) / 3)`, which is `length() / sqrt(3)`.
+    pub fn rms(&self) -> f32 {
+        return (self.length_squared() / 3.0).sqrt();
+    }
+
+    // Scales this vector down to length `max` if it's longer than
+    // that, keeping its direction. Shorter vectors are returned
+    // unchanged.
+    pub fn clamp_length_max(self, max: f32) -> Vec3 {
+        let length = self.length();
+        if length <= max {
+            return self;
+        }
+        return self * (max / length);
+    }
+
+    // The same as `clamp_length_max`, under the name many game
+    // engines use for it.
+    pub fn clamp_magnitude(self, max: f32) -> Vec3 {
+        return self.clamp_length_max(max);
+    }
+
+    // Returns the parameter `t` of the point on the infinite line
+    // `line_point + line_dir * t` that is closest to `point`.
+    // `line_dir` doesn't need to be normalized.
+    pub fn project_param_on_line(point: &Vec3, line_point: &Vec3, line_dir: &Vec3) -> f32 {
+        return Vec3::dot(&(point - line_point), line_dir) / line_dir.length_squared();
+    }
+
+    // Mirrors this point through the infinite line through
+    // `line_point` along `line_dir`, which is the same as rotating it
+    // 180 degrees about the line. `line_dir` doesn't need to be
+    // normalized.
+    pub fn mirror_across_line(&self, line_point: &Vec3, line_dir: &Vec3) -> Vec3 {
+        let t = Vec3::project_param_on_line(self, line_point, line_dir);
+        let closest = line_point + line_dir * t;
+        return closest * 2.0 - self;
+    }
+
+    // Returns the integer coordinates of the grid cell containing
+    // this point, for a grid of cubes with sides of `cell_size`
+    // starting at the origin. Each coordinate is
+    // `floor(component / cell_size)`, so negative positions fall into
+    // negative cells. Panics if `cell_size` isn't positive.
+    pub fn grid_cell(&self, cell_size: f32) -> [i32; 3] {
+        assert!(cell_size > 0.0, "grid_cell requires a positive cell_size");
+        let cell = |value: f32| (value / cell_size).floor() as i32;
+        return [cell(self.x), cell(self.y), cell(self.z)];
+    }
+
+    // Packs this vector into a u32 using the 10:10:10:2 layout, as a
+    // compact vertex normal format. Each component is clamped to
+    // [-1, 1] and stored as a 10-bit signed normalized integer, with
+    // x in bits 0-9, y in bits 10-19 and z in bits 20-29. The top two
+    // bits are left as zero.
+    pub fn pack_1010102(&self) -> u32 {
+        let pack = |value: f32| ((value.clamp(-1.0, 1.0) * 511.0).round() as i32 as u32) & 0x3ff;
+        return pack(self.x) | pack(self.y) << 10 | pack(self.z) << 20;
+    }
+
+    // The inverse of `pack_1010102`. Each component comes back within
+    // half a step of 1/511 of the original.
+    pub fn unpack_1010102(packed: u32) -> Vec3 {
+        // Shift each 10-bit field to the top of an i32 and back down so
+        // that its sign bit gets extended.
+        let unpack = |shift: u32| {
+            let value = ((packed >> shift) << 22) as i32 >> 22;
+            (value as f32 / 511.0).max(-1.0)
+        };
+        return Vec3::new(unpack(0), unpack(10), unpack(20));
+    }
+
+    // Returns a new vector whose x, y and z are taken from this
+    // vector's `x_from`, `y_from` and `z_from` components. For example
+    // `v.permute(Axis::X, Axis::Z, Axis::Y)` swaps y and z.
+    pub fn permute(&self, x_from: Axis, y_from: Axis, z_from: Axis) -> Vec3 {
+        return Vec3 {
+            x: self.component(x_from),
+            y: self.component(y_from),
+            z: self.component(z_from),
+        };
+    }
+
+    // Converts a vector from a right-handed Y-up coordinate system to
+    // a right-handed Z-up one: `(x, y, z)` becomes `(x, -z, y)`. This
+    // is a 90 degree rotation about X, so handedness is preserved.
+    pub fn y_up_to_z_up(&self) -> Vec3 {
+        return self
+            .permute(Axis::X, Axis::Z, Axis::Y)
+            .scale_axis(Axis::Y, -1.0);
+    }
+
+    // The inverse of `y_up_to_z_up`: `(x, y, z)` becomes `(x, z, -y)`.
+    pub fn z_up_to_y_up(&self) -> Vec3 {
+        return self
+            .permute(Axis::X, Axis::Z, Axis::Y)
+            .scale_axis(Axis::Z, -1.0);
+    }
+
+    // If this vector is (to within a small epsilon) one of ±X, ±Y or
+    // ±Z, returns that axis along with whether it's the positive
+    // direction. Returns `None` for any other vector.
+    pub fn as_unit_axis(&self) -> Option<(Axis, bool)> {
+        const EPSILON: f32 = 0.00001;
+        for axis in Axis::all().iter() {
+            let unit = Vec3::from(*axis);
+            if (self - unit).length() < EPSILON {
+                return Some((*axis, true));
+            }
+            if (self + unit).length() < EPSILON {
+                return Some((*axis, false));
+            }
+        }
+        return None;
+    }
+
+    // Orders two vectors by their length, for use with `sort_by`, e.g.
+    // `vectors.sort_by(|a, b| a.cmp_by_length(b))`. This compares
+    // squared lengths, so no square roots are taken. Vectors whose
+    // length is NaN are ordered after all others.
+    pub fn cmp_by_length(&self, other: &Vec3) -> Ordering {
+        let a = self.length_squared();
+        let b = other.length_squared();
+        return a
+            .partial_cmp(&b)
+            .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()));
+    }
+
+    // Clamps every component to the range [lo, hi]. Panics if `lo` is
+    // greater than `hi` or either is NaN, as `f32::clamp` does.
+    pub fn clamp_scalar(&self, lo: f32, hi: f32) -> Vec3 {
+        return self.map(|value| value.clamp(lo, hi));
+    }
+
+    // Rotates this vector by `steps` quarter turns about `axis`,
+    // counter-clockwise when looking down the axis (right hand rule).
+    // Negative steps rotate the other way. Only swaps and negates
+    // components, so there's no floating point error.
+    pub fn rotate_90(&self, axis: Axis, steps: i32) -> Vec3 {
+        let mut result = *self;
+        for _ in 0..steps.rem_euclid(4) {
+            result = match axis {
+                Axis::X => Vec3::new(result.x, -result.z, result.y),
+                Axis::Y => Vec3::new(result.z, result.y, -result.x),
+                Axis::Z => Vec3::new(-result.y, result.x, result.z),
+            };
+        }
+        return result;
+    }
+
+    // Evaluates the cubic Hermite curve from `p0` to `p1`, with
+    // tangent `m0` at `p0` and `m1` at `p1`. Returns `p0` at `t = 0`
+    // and `p1` at `t = 1`. With zero tangents this eases in and out
+    // of the endpoints like a smoothstep.
+    pub fn hermite(p0: &Vec3, m0: &Vec3, p1: &Vec3, m1: &Vec3, t: f32) -> Vec3 {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+        return p0 * h00 + m0 * h10 + p1 * h01 + m1 * h11;
+    }
+
+    // Returns the mean of `points` using Kahan (compensated)
+    // summation, which keeps the rounding error from growing with the
+    // number of points. Use this over a plain running sum when
+    // averaging a very large number of vectors. Returns `Vec3::ZERO`
+    // for an empty slice.
+    pub fn kahan_mean(points: &[Vec3]) -> Vec3 {
+        if points.is_empty() {
+            return Vec3::ZERO;
+        }
+
+        let mut sum = Vec3::ZERO;
+        let mut compensation = Vec3::ZERO;
+        for point in points {
+            let y = point - compensation;
+            let t = sum + y;
+            compensation = (t - sum) - y;
+            sum = t;
+        }
+        return sum / points.len() as f32;
+    }
+
+    // Returns the components widened to f64, for passing to APIs
+    // that work in double precision. This conversion is exact.
+    pub fn to_f64_array(&self) -> [f64; 3] {
+        return [self.x as f64, self.y as f64, self.z as f64];
+    }
+
+    // Returns the squared distance from `point` to the closest point
+    // on the segment from `a` to `b`. Cheaper than taking the actual
+    // distance since it never takes a square root, and fine for
+    // comparisons.
+    pub fn distance_squared_to_segment(point: &Vec3, a: &Vec3, b: &Vec3) -> f32 {
+        return (point - Vec3::closest_point_on_segment(point, a, b)).length_squared();
+    }
+
+    // Hashes this vector after snapping each component to the
+    // nearest multiple of `epsilon`, so that vectors which differ only
+    // by tiny floating point errors usually hash the same. This is a
+    // tradeoff: two vectors closer than `epsilon` can still hash
+    // differently if they round to different grid points (i.e. they
+    // sit either side of a cell boundary), and any two vectors in the
+    // same cell collide. Pick `epsilon` to match the precision you
+    // care about. The hash isn't guaranteed to be stable across Rust
+    // versions, so don't persist it.
+    pub fn quantized_hash(&self, epsilon: f32) -> u64 {
+        let snap = |value: f32| (value / epsilon).round() as i64;
+        let mut hasher = DefaultHasher::new();
+        (snap(self.x), snap(self.y), snap(self.z)).hash(&mut hasher);
+        return hasher.finish();
+    }
+
+    // Parses a hex color like `"#FF8000"` (the `#` is optional) into
+    // a vector with r, g and b in x, y and z, each in [0, 1].
+    pub fn from_hex(s: &str) -> Result<Vec3, ParseHexError> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        if digits.chars().count() != 6 {
+            return Err(ParseHexError::InvalidLength(digits.chars().count()));
+        }
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseHexError::InvalidDigit);
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap() as f32 / 255.0;
+        return Ok(Vec3::new(channel(0), channel(2), channel(4)));
+    }
+
+    // Formats this vector as a `#RRGGBB` hex color, treating x, y and
+    // z as r, g and b in [0, 1]. Components outside that range are
+    // clamped.
+    pub fn to_hex(&self) -> String {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        return format!(
+            "#{:02X}{:02X}{:02X}",
+            channel(self.x),
+            channel(self.y),
+            channel(self.z)
+        );
+    }
+
+    // Splits this incident direction at a surface into its reflected
+    // and refracted rays, returning `(reflected, refracted,
+    // reflectance)`. `eta` is the ratio of refractive indices
+    // `n_incident / n_transmitted`, e.g. 1.0 / 1.5 going from air into
+    // glass. `refracted` is `None` on total internal reflection, in
+    // which case the reflectance is 1.0; otherwise the reflectance is
+    // Schlick's approximation of the Fresnel term. This vector and
+    // `normal` are assumed to be normalized, with `normal` pointing
+    // against this vector (out of the surface it's hitting).
+    pub fn fresnel_split(&self, normal: &Vec3, eta: f32) -> (Vec3, Option<Vec3>, f32) {
+        let reflected = self.reflect(normal);
+        let cos_incident = -Vec3::dot(self, normal);
+        let sin_transmitted_squared = eta * eta * (1.0 - cos_incident * cos_incident);
+        if sin_transmitted_squared > 1.0 {
+            return (reflected, None, 1.0);
+        }
+
+        let cos_transmitted = (1.0 - sin_transmitted_squared).sqrt();
+        let refracted = self * eta + normal * (eta * cos_incident - cos_transmitted);
+
+        // Schlick's approximation needs the cosine of the angle on the
+        // less dense side of the boundary.
+        let r0 = ((1.0 - eta) / (1.0 + eta)).powi(2);
+        let cos_theta = if eta <= 1.0 {
+            cos_incident
+        } else {
+            cos_transmitted
+        };
+        let reflectance = r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5);
+        return (reflected, Some(refracted), reflectance);
+    }
+
+    // Returns the per-component (population) variance of `points`
+    // about their mean. An empty slice or a single point has no
+    // spread and returns `Vec3::ZERO`.
+    pub fn variance(points: &[Vec3]) -> Vec3 {
+        if points.len() < 2 {
+            return Vec3::ZERO;
+        }
+
+        let mean = Vec3::kahan_mean(points);
+        let mut sum = Vec3::ZERO;
+        for point in points {
+            sum += (point - mean).square();
+        }
+        return sum / points.len() as f32;
+    }
+
+    // Returns the unit direction along which `points` are most spread
+    // out, i.e. the first principal component. It's found by power
+    // iteration on the covariance matrix. The sign of the result is
+    // arbitrary. If there are fewer than two distinct points there's
+    // no spread to measure, and +X is returned.
+    pub fn principal_axis(points: &[Vec3]) -> Vec3 {
+        const ITERATIONS: usize = 64;
+        let fallback = Vec3::new(1.0, 0.0, 0.0);
+        if points.len() < 2 {
+            return fallback;
+        }
+
+        // The rows of the (symmetric) covariance matrix
+        let mean = Vec3::kahan_mean(points);
+        let mut rows = [Vec3::ZERO; 3];
+        for point in points {
+            let d = point - mean;
+            rows[0] += d * d.x;
+            rows[1] += d * d.y;
+            rows[2] += d * d.z;
+        }
+
+        let multiply = |v: &Vec3| {
+            Vec3::new(
+                Vec3::dot(&rows[0], v),
+                Vec3::dot(&rows[1], v),
+                Vec3::dot(&rows[2], v),
+            )
+        };
+
+        // A single starting vector can be an eigenvector for a smaller
+        // eigenvalue, in which case power iteration never leaves it.
+        // The principal axis lies in the span of the columns, so
+        // iterating from each column and keeping the result with the
+        // largest Rayleigh quotient `v·Cv` always finds it.
+        let mut best = fallback;
+        let mut best_quotient = 0.0;
+        for start in &rows {
+            let mut axis = *start;
+            for _ in 0..ITERATIONS {
+                let length = axis.length();
+                if length == 0.0 {
+                    break;
+                }
+                axis = multiply(&(axis / length));
+            }
+
+            if let Ok(axis) = axis.checked_normalize() {
+                let quotient = Vec3::dot(&axis, &multiply(&axis));
+                if quotient > best_quotient {
+                    best = axis;
+                    best_quotient = quotient;
+                }
+            }
+        }
+        return best;
+    }
+
+    // Swaps the components along axes `a` and `b` in place. Swapping
+    // an axis with itself does nothing.
+    pub fn swap_components(&mut self, a: Axis, b: Axis) {
+        let value_a = self.component(a);
+        let value_b = self.component(b);
+        self.set_component(a, value_b);
+        self.set_component(b, value_a);
+    }
+
+    // Like `normalize`, but guaranteed to return a finite vector:
+    // returns `fallback` instead if this vector is zero or has a NaN
+    // or infinite component. Very large vectors are scaled down
+    // before normalizing so their squared length can't overflow.
+    pub fn normalize_or(self, fallback: Vec3) -> Vec3 {
+        if !(self.x.is_finite() && self.y.is_finite() && self.z.is_finite()) {
+            return fallback;
+        }
+
+        let scale = self.x.abs().max(self.y.abs()).max(self.z.abs());
+        if scale == 0.0 {
+            return fallback;
+        }
+        return (self / scale).normalize();
+    }
+
+    // Returns, per component, whichever of `self` and `other` has the
+    // larger absolute value, keeping its sign. Ties go to `self`.
+    pub fn max_abs(&self, other: &Vec3) -> Vec3 {
+        let pick = |a: f32, b: f32| if b.abs() > a.abs() { b } else { a };
+        return Vec3 {
+            x: pick(self.x, other.x),
+            y: pick(self.y, other.y),
+            z: pick(self.z, other.z),
+        };
+    }
+
+    // Returns, per component, whichever of `self` and `other` has the
+    // smaller absolute value, keeping its sign. Ties go to `self`.
+    pub fn min_abs(&self, other: &Vec3) -> Vec3 {
+        let pick = |a: f32, b: f32| if b.abs() < a.abs() { b } else { a };
+        return Vec3 {
+            x: pick(self.x, other.x),
+            y: pick(self.y, other.y),
+            z: pick(self.z, other.z),
+        };
+    }
+
+    // Returns the unit axis and angle in radians of the rotation that
+    // takes the unit vector `from` onto the unit vector `to`. When
+    // the vectors are parallel the angle is 0.0 and any axis would
+    // do; when they're opposite the angle is π and any axis
+    // perpendicular to them would do. In both cases an axis
+    // perpendicular to `from` is returned.
+    pub fn rotation_between(from: &Vec3, to: &Vec3) -> (Vec3, f32) {
+        let axis = Vec3::cross(from, to);
+        let length = axis.length();
+        if length > 0.000001 {
+            return (axis / length, from.angle_between_unit(to));
+        }
+
+        let perpendicular = from.any_perpendicular();
+        let angle = if Vec3::dot(from, to) > 0.0 {
+            0.0
+        } else {
+            std::f32::consts::PI
+        };
+        return (perpendicular, angle);
+    }
+
+    // Rotates this vector by the Euler angles in `angles` (radians),
+    // applying the rotation about the X axis first, then Y, then Z.
+    // The axes are the fixed world axes (extrinsic rotations) and
+    // each rotation is counter-clockwise when looking down its axis,
+    // following the right hand rule.
+    pub fn rotate_euler_xyz(&self, angles: &Vec3) -> Vec3 {
+        let (sin_x, cos_x) = angles.x.sin_cos();
+        let (sin_y, cos_y) = angles.y.sin_cos();
+        let (sin_z, cos_z) = angles.z.sin_cos();
+
+        let v = Vec3::new(
+            self.x,
+            self.y * cos_x - self.z * sin_x,
+            self.y * sin_x + self.z * cos_x,
+        );
+        let v = Vec3::new(v.x * cos_y + v.z * sin_y, v.y, v.z * cos_y - v.x * sin_y);
+        return Vec3::new(v.x * cos_z - v.y * sin_z, v.x * sin_z + v.y * cos_z, v.z);
+    }
+
+    // Returns `count` evenly spaced points from `a` to `b`, including
+    // both endpoints. With `count` of 1 the result is just `[a]`, and
+    // with 0 it's empty.
+    pub fn lerp_samples(a: &Vec3, b: &Vec3, count: usize) -> Vec<Vec3> {
+        if count < 2 {
+            return vec![*a; count];
+        }
+
+        let last = count - 1;
+        let mut samples: Vec<Vec3> = (0..last)
+            .map(|i| Vec3::lerp(a, b, i as f32 / last as f32))
+            .collect();
+        // Push `b` itself rather than lerping to it, so the last
+        // sample is exact.
+        samples.push(*b);
+        return samples;
+    }
+
+    // Splits this displacement into a unit direction and a distance,
+    // so that `direction * distance` gives it back. The zero vector
+    // has no direction and returns `(Vec3::ZERO, 0.0)`.
+    pub fn split_direction_distance(&self) -> (Vec3, f32) {
+        return (self.normalize_or(Vec3::ZERO), self.length());
+    }
+}
+
+// Builds an orthonormal frame from three nearly-orthonormal vectors
+// using Gram-Schmidt. `a` keeps its direction, `b` is made
+// perpendicular to `a`, and `c` is made perpendicular to both. Use
+// this to clean up a basis that has drifted after many incremental
+// rotations.
+pub fn gram_schmidt(a: Vec3, b: Vec3, c: Vec3) -> (Vec3, Vec3, Vec3) {
+    let u = a.normalize();
+    let v = b.orthogonalize(&u).normalize();
+    let w = c.orthogonalize(&u).orthogonalize(&v).normalize();
+    return (u, v, w);
+}
+
+// A line segment stored as its start point and the offset to its
+// end point. Use this instead of `Vec3::lerp` when sampling the same
+// segment many times, so `b - a` is only computed once.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub origin: Vec3,
+    pub delta: Vec3,
+}
+
+impl Segment {
+    pub fn new(a: Vec3, b: Vec3) -> Segment {
+        Segment {
+            origin: a,
+            delta: b - a,
+        }
+    }
+
+    // Returns the point `t` of the way along the segment, so `at(0.0)`
+    // is the start point and `at(1.0)` the end point.
+    pub fn at(&self, t: f32) -> Vec3 {
+        return self.origin + self.delta * t;
+    }
+}
+
+// This macro helps us implement math operators on Vector3
+// in such a way that it handles binary operators on any
+// combination of Vec3, &Vec3 and f32.
+macro_rules! impl_binary_operations {
+  // $VectorType is something like `Vec3`
+  // $Operation is something like `Add`
+  // $op_fn is something like `add`
+  // $op_symbol is something like `+`
+  ($VectorType:ident $Operation:ident $op_fn:ident $op_symbol:tt) => {
+    // Implement a + b where a and b are both of type &VectorType.
+    // Lower down we'll implement cases where either a or b - or both
+    // - are values by forwarding through to this implementation.
+    impl<'a, 'b> $Operation<&'a $VectorType> for &'b $VectorType {
+      type Output = $VectorType;
+      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
+        $VectorType {
+          x: self.x $op_symbol other.x,
+          y: self.y $op_symbol other.y,
+          z: self.z $op_symbol other.z,
+        }
+      }
+    }
+
+    // Implement a + b for the cases...
+    //
+    //   a: $VectorType,  b: &$VectorType
+    //   a: &$VectorType, b: $VectorType
+    //   a: $VectorType, b: $VectorType
+    //
+    // In each case we forward through to the implementation above.
+    impl $Operation<$VectorType> for $VectorType {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self, other: $VectorType) -> $VectorType {
+        &self $op_symbol &other
+      }
+    }
+
+    impl<'a> $Operation<&'a $VectorType> for $VectorType {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
+        &self $op_symbol other
+      }
+    }
+
+    impl<'a> $Operation<$VectorType> for &'a $VectorType {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self, other: $VectorType) -> $VectorType {
+        self $op_symbol &other
+      }
+    }
+
+    // Implement a + b where a is type &$VectorType and b is type f32
+    impl<'a> $Operation<f32> for &'a $VectorType {
+      type Output = $VectorType;
+
+      fn $op_fn(self, other: f32) -> $VectorType {
+        $VectorType {
+          x: self.x $op_symbol other,
+          y: self.y $op_symbol other,
+          z: self.z $op_symbol other
+        }
+      }
+    }
+
+    // Implement a + b where a is $VectorType and b is f32 by
+    // forwarding the logic to the implementation above.
+    impl $Operation<f32> for $VectorType {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self, other: f32) -> $VectorType {
+        &self $op_symbol other
+      }
+    }
+
+    // Implement a + b where a is type f32 and b is type &$VectorType.
+    // We can't forward this to `b + a` because that's only correct
+    // for commutative operations. For example `2.0 - v` must compute
+    // `2.0 - v.x` rather than `v.x - 2.0`.
+    impl<'a> $Operation<&'a $VectorType> for f32 {
+      type Output = $VectorType;
+
+      fn $op_fn(self, other: &'a $VectorType) -> $VectorType {
+        $VectorType {
+          x: self $op_symbol other.x,
+          y: self $op_symbol other.y,
+          z: self $op_symbol other.z
+        }
+      }
+    }
+
+    // Implement a + b where a is f32 and b is $VectorType by
+    // forwarding to the implementation above.
+    impl $Operation<$VectorType> for f32 {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self, other: $VectorType) -> $VectorType {
+        self $op_symbol &other
+      }
+    }
+  };
+}
+
+// It also implements unary operators like - a where a is of
+// type Vec3 or &Vec3.
+macro_rules! impl_unary_operations {
+  // $VectorType is something like `Vec3`
+  // $Operation is something like `Neg`
+  // $op_fn is something like `neg`
+  // $op_symbol is something like `-`
+  ($VectorType:ident $Operation:ident $op_fn:ident $op_symbol:tt) => {
+
+    // Implement the unary operator for references
+    impl<'a> $Operation for &'a $VectorType {
+      type Output = $VectorType;
+
+      fn $op_fn(self) -> Vec3 {
+        $VectorType {
+          x: $op_symbol self.x,
+          y: $op_symbol self.y,
+          z: $op_symbol self.z,
+        }
+      }
+    }
+
+    // Have the operator on values forward through to the implementation
+    // above
+    impl $Operation for $VectorType {
+      type Output = $VectorType;
+
+      #[inline]
+      fn $op_fn(self) -> Vec3 {
+        $op_symbol &self
+      }
+    }
+  };
+}
+
+// Implement add-assignment operators like a += b where a and
+// b is either &Vec3, Vec3 or f32 (in this case a is always of type
+// &mut Vec3).
+macro_rules! impl_op_assign {
+  // $VectorType is something like `Vec3`
+  // $OperationAssign is something like `AddAssign`
+  // $op_fn is something like `add_assign`
+  // $op_symbol is something like `+=`
+  ($VectorType:ident $OperationAssign:ident $op_fn:ident $op_symbol:tt) => {
+    // Implement $OperationAssign for RHS &Vec3
+    impl<'a> $OperationAssign<&'a $VectorType> for $VectorType {
+      fn $op_fn(&mut self, other: &'a $VectorType) {
+        *self = $VectorType {
+          x: self.x $op_symbol other.x,
+          y: self.y $op_symbol other.y,
+          z: self.z $op_symbol other.z,
+        };
+      }
+    }
+
+    // Implement $OperationAssign for RHS Vec3 by forwarding through to the
+    // implementation above
+    impl $OperationAssign for $VectorType {
+      #[inline]
+      fn $op_fn(&mut self, other: $VectorType) {
+        *self = *self $op_symbol &other
+      }
+    }
+
+    // Implement $OperationAssign for RHS f32, applying the scalar to
+    // every component
+    impl $OperationAssign<f32> for $VectorType {
+      #[inline]
+      fn $op_fn(&mut self, other: f32) {
+        *self = *self $op_symbol other
+      }
+    }
+  };
+}
+
+impl_binary_operations!(Vec3 Add add +);
+impl_op_assign!(Vec3 AddAssign add_assign +);
+
+impl_binary_operations!(Vec3 Sub sub -);
+impl_op_assign!(Vec3 SubAssign sub_assign -);
+impl_unary_operations!(Vec3 Neg neg -);
+
+impl_binary_operations!(Vec3 Mul mul *);
+impl_op_assign!(Vec3 MulAssign mul_assign *);
+
+impl_binary_operations!(Vec3 Div div /);
+impl_op_assign!(Vec3 DivAssign div_assign /);
+
+// Converts an axis into the unit vector pointing along it, so
+// `Vec3::from(Axis::Y)` is `(0, 1, 0)`.
+impl From<Axis> for Vec3 {
+    fn from(axis: Axis) -> Vec3 {
+        match axis {
+            Axis::X => Vec3::new(1.0, 0.0, 0.0),
+            Axis::Y => Vec3::new(0.0, 1.0, 0.0),
+            Axis::Z => Vec3::new(0.0, 0.0, 1.0),
+        }
+    }
+}
+
+// Formats a vector as `x, y, z`, which can be parsed back with
+// `str::parse`. Any precision passed to the formatter (as in
+// `format!("{:.2}", v)`) is applied to each component.
+impl fmt::Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.x, f)?;
+        write!(f, ", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        write!(f, ", ")?;
+        fmt::Display::fmt(&self.z, f)
+    }
+}
+
+// The error returned when parsing a Vec3 from a string fails.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseVec3Error {
+    // The string didn't contain exactly three components. Holds the
+    // number of components that were found.
+    WrongComponentCount(usize),
+    // One of the components wasn't a valid f32.
+    InvalidComponent(ParseFloatError),
+}
+
+impl fmt::Display for ParseVec3Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseVec3Error::WrongComponentCount(count) => {
+                write!(f, "expected 3 components but found {}", count)
+            }
+            ParseVec3Error::InvalidComponent(err) => write!(f, "invalid component: {}", err),
+        }
+    }
+}
+
+impl Error for ParseVec3Error {}
+
+// The error returned by `Vec3::checked_normalize` when the vector
+// has zero length and so has no direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizeError;
+
+impl fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot normalize a zero-length vector")
+    }
+}
+
+impl Error for NormalizeError {}
+
+// The error returned by `Vec3::from_hex` for strings that aren't a
+// valid `#RRGGBB` color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseHexError {
+    // The string didn't have exactly six digits after the optional
+    // `#`. Holds the number of characters that were found.
+    InvalidLength(usize),
+    // The string contained a character that isn't a hex digit.
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseHexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseHexError::InvalidLength(length) => {
+                write!(f, "expected 6 hex digits but found {} characters", length)
+            }
+            ParseHexError::InvalidDigit => write!(f, "invalid hex digit"),
+        }
+    }
+}
+
+impl Error for ParseHexError {}
+
+// Parses a vector from three numbers separated either by commas,
+// as in `"1.0, 2.0, 3.0"`, or by whitespace, as in `"1 2 3"`.
+impl FromStr for Vec3 {
+    type Err = ParseVec3Error;
+
+    fn from_str(s: &str) -> Result<Vec3, ParseVec3Error> {
+        let components: Vec<&str> = if s.contains(',') {
+            s.split(',').map(|part| part.trim()).collect()
+        } else {
+            s.split_whitespace().collect()
+        };
+        if components.len() != 3 {
+            return Err(ParseVec3Error::WrongComponentCount(components.len()));
+        }
+
+        let parse = |part: &str| {
+            part.parse::<f32>()
+                .map_err(ParseVec3Error::InvalidComponent)
+        };
+        return Ok(Vec3::new(
+            parse(components[0])?,
+            parse(components[1])?,
+            parse(components[2])?,
+        ));
+    }
+}
+
+// Conversions to and from the `mint` interop types so that Vec3
+// can be passed to and from other math libraries. Enabled with
+// the `mint` feature.
+#[cfg(feature = "mint")]
+impl From<mint::Vector3<f32>> for Vec3 {
+    fn from(v: mint::Vector3<f32>) -> Vec3 {
+        Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Vec3> for mint::Vector3<f32> {
+    fn from(v: Vec3) -> mint::Vector3<f32> {
+        mint::Vector3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+impl mint::IntoMint for Vec3 {
+    type MintType = mint::Vector3<f32>;
+}
+
+// Vec3 is `#[repr(C)]` and made up of three f32s with no padding,
+// so it's safe to cast to and from raw bytes (e.g. when uploading
+// vertex data to the GPU). Enabled with the `bytemuck` feature.
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Vec3 {}
+
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Vec3 {}
+
+#[cfg(test)]
+#[allow(clippy::op_ref, clippy::needless_borrows_for_generic_args)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add() {
+        let a = Vec3::new(0.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(&a + &b, Vec3::new(3.0, 5.0, 7.0));
+        assert_eq!(a + &b, Vec3::new(3.0, 5.0, 7.0));
+        assert_eq!(&a + b, Vec3::new(3.0, 5.0, 7.0));
+        assert_eq!(a + b, Vec3::new(3.0, 5.0, 7.0));
+
+        // Test for RHS value type
+        {
+            let mut c = Vec3::ONE;
+            c += a;
+            assert_eq!(c, Vec3::new(1.0, 2.0, 3.0));
+        }
+
+        // Test for RHS borrowed reference
+        {
+            let mut c = Vec3::ONE;
+            c += &a;
+            assert_eq!(c, Vec3::new(1.0, 2.0, 3.0));
+        }
+    }
+
+    #[test]
+    fn subtract() {
+        let a = Vec3::new(0.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(&a - &b, Vec3::new(-3.0, -3.0, -3.0));
+        assert_eq!(a - &b, Vec3::new(-3.0, -3.0, -3.0));
+        assert_eq!(&a - b, Vec3::new(-3.0, -3.0, -3.0));
+        assert_eq!(a - b, Vec3::new(-3.0, -3.0, -3.0));
+
+        // Test for RHS value type
+        {
+            let mut c = Vec3::ONE;
+            c -= a;
+            assert_eq!(c, Vec3::new(1.0, 0.0, -1.0));
+        }
+
+        // Test for RHS borrowed reference
+        {
+            let mut c = Vec3::ONE;
+            c -= &a;
+            assert_eq!(c, Vec3::new(1.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn multiply() {
+        let a = Vec3::new(0.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(&a * &b, Vec3::new(0.0, 4.0, 10.0));
+        assert_eq!(a * &b, Vec3::new(0.0, 4.0, 10.0));
+        assert_eq!(&a * b, Vec3::new(0.0, 4.0, 10.0));
+        assert_eq!(a * b, Vec3::new(0.0, 4.0, 10.0));
+
+        // Test for RHS value type
+        {
+            let mut c = Vec3::from_float(2.0);
+            c *= a;
+            assert_eq!(c, 2.0 * a);
+        }
+
+        // Test for RHS borrowed reference
+        {
+            let mut c = Vec3::from_float(2.0);
+            c *= &a;
+            assert_eq!(c, 2.0 * a);
+        }
+    }
+
+    #[test]
+    fn divide() {
+        let a = Vec3::new(1.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(&a / &b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
+        assert_eq!(a / &b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
+        assert_eq!(&a / b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
+        assert_eq!(a / b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
+
+        // Test for RHS value type
+        {
+            let mut c = Vec3::ONE;
+            c /= a;
+            assert_eq!(c, Vec3::new(1.0, 1.0, 0.5));
+        }
+
+        // Test for RHS borrowed reference
         {
-            let mut c = Vec3::from_float(2.0);
-            c *= &a;
-            assert_eq!(c, 2.0 * a);
+            let mut c = Vec3::ONE;
+            c /= &a;
+            assert_eq!(c, Vec3::new(1.0, 1.0, 0.5));
+        }
+    }
+
+    #[test]
+    fn dot() {
+        let a = Vec3::new(2.0, 3.0, 5.0);
+        let b = Vec3::new(7.0, 11.0, 13.0);
+        assert_eq!(Vec3::dot(&a, &b), 2.0 * 7.0 + 3.0 * 11.0 + 5.0 * 13.0);
+    }
+
+    #[test]
+    fn cross() {
+        let a = Vec3::new(1.0, 0.0, 0.0);
+        let b = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(Vec3::cross(&a, &b), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn length() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        assert_eq!(a.length(), ((3.0 * 3.0 + 2.0 * 2.0 + 1.0 * 1.0) as f32).sqrt());
+
+        let b = Vec3::from_float(0.0);
+        assert_eq!(b.length(), 0.0);
+    }
+
+    #[test]
+    fn normalize() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        let len = a.length();
+        assert!((a.normalize().length() - 1.0).abs() < 0.01);
+        assert_eq!(a.normalize(), a / len);
+    }
+
+    #[test]
+    fn component() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        assert_eq!(a.component(Axis::X), a.x);
+        assert_eq!(a.component(Axis::Y), a.y);
+        assert_eq!(a.component(Axis::Z), a.z);
+    }
+
+    #[test]
+    fn set_component() {
+        let mut a = Vec3::new(3.0, 2.0, 1.0);
+        a.set_component(Axis::X, 4.0);
+        assert_eq!(a, Vec3::new(4.0, 2.0, 1.0));
+
+        a.set_component(Axis::Y, 5.0);
+        assert_eq!(a, Vec3::new(4.0, 5.0, 1.0));
+
+        a.set_component(Axis::Z, 6.0);
+        assert_eq!(a, Vec3::new(4.0, 5.0, 6.0));
+    }
+
+    #[test]
+    fn with_component() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        assert_eq!(a.with_x(4.0), Vec3::new(4.0, 2.0, 1.0));
+        assert_eq!(a.with_y(4.0), Vec3::new(3.0, 4.0, 1.0));
+        assert_eq!(a.with_z(4.0), Vec3::new(3.0, 2.0, 4.0));
+    }
+
+    #[test]
+    fn min() {
+      let tiny_x = Vec3::new(0.00001, 1000.0, 1000.0);
+      let tiny_y = Vec3::new(1000.0, 0.00001, 1000.0);
+      let tiny_z = Vec3::new(1000.0, 1000.0, 0.00001);
+      assert_eq!(tiny_x.min(&tiny_y).min(&tiny_z), Vec3::from_float(0.00001));
+    }
+
+    #[test]
+    fn max() {
+      let big_x = Vec3::new(1000.0, 0.00001, 0.00001);
+      let big_y = Vec3::new(0.00001, 1000.0, 0.00001);
+      let big_z = Vec3::new(0.00001, 0.00001, 1000.0);
+      assert_eq!(big_x.max(&big_y).max(&big_z), Vec3::from_float(1000.0));
+    }
+
+    #[test]
+    fn is_within_bounds() {
+        let min = Vec3::ZERO;
+        let max = Vec3::ONE;
+        assert!(Vec3::from_float(0.5).is_within_bounds(&min, &max));
+        assert!(Vec3::new(1.0, 0.5, 0.0).is_within_bounds(&min, &max));
+        assert!(!Vec3::new(0.5, 1.5, 0.5).is_within_bounds(&min, &max));
+    }
+
+    #[test]
+    fn closest_point_on_segment() {
+        let a = Vec3::ZERO;
+        let b = Vec3::new(2.0, 0.0, 0.0);
+
+        // Projects onto the interior of the segment
+        let p = Vec3::new(1.5, 3.0, 0.0);
+        assert_eq!(
+            Vec3::closest_point_on_segment(&p, &a, &b),
+            Vec3::new(1.5, 0.0, 0.0)
+        );
+
+        // Clamped to either endpoint
+        let before = Vec3::new(-1.0, 1.0, 0.0);
+        assert_eq!(Vec3::closest_point_on_segment(&before, &a, &b), a);
+        let after = Vec3::new(5.0, -1.0, 2.0);
+        assert_eq!(Vec3::closest_point_on_segment(&after, &a, &b), b);
+
+        // Degenerate segment
+        assert_eq!(Vec3::closest_point_on_segment(&p, &b, &b), b);
+    }
+
+    #[test]
+    fn closest_point_on_ray() {
+        let origin = Vec3::new(1.0, 0.0, 0.0);
+        let direction = Vec3::new(0.0, 1.0, 0.0);
+
+        let in_front = Vec3::new(3.0, 2.0, -1.0);
+        assert_eq!(
+            Vec3::closest_point_on_ray(&in_front, &origin, &direction),
+            Vec3::new(1.0, 2.0, 0.0)
+        );
+
+        let behind = Vec3::new(3.0, -2.0, -1.0);
+        assert_eq!(
+            Vec3::closest_point_on_ray(&behind, &origin, &direction),
+            origin
+        );
+    }
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint_round_trip() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let m: mint::Vector3<f32> = a.into();
+        assert_eq!((m.x, m.y, m.z), (1.0, 2.0, 3.0));
+        assert_eq!(Vec3::from(m), a);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn bytemuck_cast() {
+        let vectors = [Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)];
+        let bytes: &[u8] = bytemuck::cast_slice(&vectors);
+        assert_eq!(bytes.len(), 2 * 3 * 4);
+
+        let back: &[Vec3] = bytemuck::cast_slice(bytes);
+        assert_eq!(back, &vectors[..]);
+    }
+
+    #[test]
+    fn reflect() {
+        let incoming = Vec3::new(1.0, -1.0, 0.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(incoming.reflect(&normal), Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn reflect_oriented() {
+        let incoming = Vec3::new(1.0, -1.0, 0.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let down = -up;
+        assert_eq!(incoming.reflect_oriented(&up), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(incoming.reflect_oriented(&down), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(incoming.reflect_oriented(&down), incoming.reflect(&up));
+    }
+
+    #[test]
+    fn distance_to_line() {
+        let point = Vec3::new(5.0, 3.0, 4.0);
+        let x_axis = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(Vec3::distance_to_line(&point, &Vec3::ZERO, &x_axis), 5.0);
+        assert_eq!(Vec3::distance_to_line(&x_axis, &Vec3::ZERO, &x_axis), 0.0);
+    }
+
+    #[test]
+    fn signed_distance_to_plane() {
+        let plane_point = Vec3::new(0.0, 1.0, 0.0);
+        let plane_normal = Vec3::new(0.0, 1.0, 0.0);
+
+        let above = Vec3::new(3.0, 4.0, -2.0);
+        let below = Vec3::new(3.0, -1.0, -2.0);
+        let on = Vec3::new(3.0, 1.0, -2.0);
+        assert_eq!(
+            Vec3::signed_distance_to_plane(&above, &plane_point, &plane_normal),
+            3.0
+        );
+        assert_eq!(
+            Vec3::signed_distance_to_plane(&below, &plane_point, &plane_normal),
+            -2.0
+        );
+        assert_eq!(
+            Vec3::signed_distance_to_plane(&on, &plane_point, &plane_normal),
+            0.0
+        );
+    }
+
+    #[test]
+    fn mul_add() {
+        let a = Vec3::new(1.5, -2.0, 0.1);
+        let b = Vec3::new(3.0, 4.0, -5.0);
+        let naive = a * 0.3 + b;
+        assert!((a.mul_add(0.3, &b) - naive).length() < 0.0001);
+    }
+
+    #[test]
+    fn dot_slices() {
+        let a = [Vec3::new(1.0, 2.0, 3.0), Vec3::new(-1.0, 0.5, 2.0)];
+        let b = [Vec3::new(4.0, 5.0, 6.0), Vec3::new(2.0, 2.0, -3.0)];
+
+        let mut expected = 0.0;
+        for i in 0..a.len() {
+            expected += Vec3::dot(&a[i], &b[i]);
+        }
+        assert_eq!(Vec3::dot_slices(&a, &b), expected);
+        assert_eq!(Vec3::dot_slices(&[], &[]), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dot_slices_length_mismatch() {
+        Vec3::dot_slices(&[Vec3::ONE], &[]);
+    }
+
+    #[test]
+    fn project_onto() {
+        let a = Vec3::new(3.0, 4.0, 5.0);
+        let b = Vec3::new(2.0, 0.0, 0.0);
+        assert_eq!(a.project_onto(&b), Vec3::new(3.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn orthogonalize() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let reference = Vec3::new(0.5, -1.0, 2.0);
+        let orthogonal = a.orthogonalize(&reference);
+        assert!(Vec3::dot(&orthogonal, &reference).abs() < 0.0001);
+    }
+
+    #[test]
+    fn signed_max_abs_component() {
+        assert_eq!(Vec3::new(1.0, -5.0, 3.0).signed_max_abs_component(), -5.0);
+        assert_eq!(Vec3::new(-1.0, 0.5, 2.0).signed_max_abs_component(), 2.0);
+        assert_eq!(Vec3::new(-7.0, 0.5, 2.0).signed_max_abs_component(), -7.0);
+    }
+
+    #[test]
+    fn project_param_on_segment() {
+        let a = Vec3::ZERO;
+        let b = Vec3::new(0.0, 0.0, 4.0);
+
+        let p = Vec3::new(1.0, 1.0, 1.0);
+        assert_eq!(
+            Vec3::project_param_on_segment(&p, &a, &b),
+            (Vec3::new(0.0, 0.0, 1.0), 0.25)
+        );
+
+        let before = Vec3::new(1.0, 1.0, -3.0);
+        assert_eq!(Vec3::project_param_on_segment(&before, &a, &b), (a, 0.0));
+
+        let after = Vec3::new(1.0, 1.0, 10.0);
+        assert_eq!(Vec3::project_param_on_segment(&after, &a, &b), (b, 1.0));
+    }
+
+    #[test]
+    fn slerp() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let halfway = Vec3::slerp(&x, &y, 0.5);
+        let expected = Vec3::new(1.0, 1.0, 0.0).normalize();
+        assert!((halfway - expected).length() < 0.0001);
+        assert!((Vec3::slerp(&x, &y, 0.0) - x).length() < 0.0001);
+        assert!((Vec3::slerp(&x, &y, 1.0) - y).length() < 0.0001);
+
+        // Opposite vectors still give a unit-length arc between them
+        let halfway = Vec3::slerp(&x, &-x, 0.5);
+        assert!((halfway.length() - 1.0).abs() < 0.0001);
+        assert!(Vec3::dot(&halfway, &x).abs() < 0.0001);
+        assert!((Vec3::slerp(&x, &-x, 1.0) + x).length() < 0.0001);
+
+        // Nearly opposite vectors bend towards the target's offset
+        let nearly_opposite = Vec3::new(-1.0, 0.0000001, 0.0);
+        let halfway = Vec3::slerp(&x, &nearly_opposite, 0.5);
+        assert!((halfway - y).length() < 0.0001);
+    }
+
+    #[test]
+    fn rotate_towards() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+
+        // Within the max angle, so we land on the target
+        assert_eq!(x.rotate_towards(&y, std::f32::consts::PI), y);
+
+        // Clamped to a 30 degree step
+        let step = std::f32::consts::PI / 6.0;
+        let rotated = x.rotate_towards(&y, step);
+        let expected = Vec3::new(step.cos(), step.sin(), 0.0);
+        assert!((rotated - expected).length() < 0.0001);
+        assert!((rotated.length() - 1.0).abs() < 0.0001);
+
+        // Opposite and nearly opposite targets are still reached
+        for target in &[-x, Vec3::new(-1.0, 0.0000001, 0.0)] {
+            let mut current = x;
+            for _ in 0..100 {
+                current = current.rotate_towards(target, 0.1);
+            }
+            assert!((current - target).length() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn bounce() {
+        let velocity = Vec3::new(2.0, -3.0, 1.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(velocity.bounce(&normal, 1.0), velocity.reflect(&normal));
+        assert_eq!(velocity.bounce(&normal, 0.0), Vec3::new(2.0, 0.0, 1.0));
+        assert_eq!(velocity.bounce(&normal, 0.5), Vec3::new(2.0, 1.5, 1.0));
+    }
+
+    #[test]
+    fn axis_all() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        let components: Vec<f32> = Axis::all().iter().map(|axis| a.component(*axis)).collect();
+        assert_eq!(components, vec![3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn axis_next() {
+        assert_eq!(Axis::X.next(), Axis::Y);
+        assert_eq!(Axis::Y.next(), Axis::Z);
+        assert_eq!(Axis::Z.next(), Axis::X);
+    }
+
+    #[test]
+    fn axis_other_two() {
+        assert_eq!(Axis::X.other_two(), (Axis::Y, Axis::Z));
+        assert_eq!(Axis::Y.other_two(), (Axis::Z, Axis::X));
+        assert_eq!(Axis::Z.other_two(), (Axis::X, Axis::Y));
+    }
+
+    #[test]
+    fn from_axis() {
+        assert_eq!(Vec3::from(Axis::X), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(Vec3::from(Axis::Y), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(Vec3::from(Axis::Z), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn cmp_masks() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(2.0, 2.0, 2.0);
+        assert_eq!(a.cmp_lt(&b), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(a.cmp_le(&b), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(a.cmp_gt(&b), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(a.cmp_ge(&b), Vec3::new(0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn select() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(2.0, 2.0, 2.0);
+        let mask = a.cmp_lt(&b);
+        assert_eq!(Vec3::select(&mask, &a, &b), Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(Vec3::select(&mask, &b, &a), Vec3::new(2.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn hadamard() {
+        let a = Vec3::new(0.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, 4.0, 5.0);
+        assert_eq!(a.hadamard(&b), a * b);
+    }
+
+    #[test]
+    fn square() {
+        let a = Vec3::new(-2.0, 3.0, 0.5);
+        assert_eq!(a.square(), a * a);
+        assert_eq!(a.square(), Vec3::new(4.0, 9.0, 0.25));
+    }
+
+    #[test]
+    fn cube() {
+        let a = Vec3::new(-2.0, 3.0, 0.5);
+        assert_eq!(a.cube(), Vec3::new(-8.0, 27.0, 0.125));
+    }
+
+    #[test]
+    fn recip_or_zero() {
+        assert_eq!(
+            Vec3::new(2.0, 0.0, -4.0).recip_or_zero(),
+            Vec3::new(0.5, 0.0, -0.25)
+        );
+        assert_eq!(Vec3::ZERO.recip_or_zero(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn signed_angle() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+        let quarter = std::f32::consts::FRAC_PI_2;
+        assert!((Vec3::signed_angle(&x, &y, &z) - quarter).abs() < 0.0001);
+        assert!((Vec3::signed_angle(&y, &x, &z) + quarter).abs() < 0.0001);
+        assert_eq!(Vec3::signed_angle(&x, &x, &z), 0.0);
+    }
+
+    #[test]
+    fn from_hsv() {
+        assert_eq!(Vec3::from_hsv(0.0, 1.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(Vec3::from_hsv(120.0, 1.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(Vec3::from_hsv(240.0, 1.0, 1.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(Vec3::from_hsv(360.0, 1.0, 1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(Vec3::from_hsv(0.0, 0.0, 0.5), Vec3::from_float(0.5));
+    }
+
+    #[test]
+    fn to_hsv() {
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0).to_hsv(), (0.0, 1.0, 1.0));
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0).to_hsv(), (120.0, 1.0, 1.0));
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0).to_hsv(), (240.0, 1.0, 1.0));
+        assert_eq!(Vec3::from_float(0.5).to_hsv(), (0.0, 0.0, 0.5));
+
+        let (h, s, v) = Vec3::new(0.2, 0.6, 0.4).to_hsv();
+        assert!((Vec3::from_hsv(h, s, v) - Vec3::new(0.2, 0.6, 0.4)).length() < 0.0001);
+    }
+
+    #[test]
+    fn sorted_components() {
+        assert_eq!(
+            Vec3::new(3.0, -1.0, 2.0).sorted_components(),
+            [-1.0, 2.0, 3.0]
+        );
+
+        let with_nan = Vec3::new(f32::NAN, 5.0, -2.0).sorted_components();
+        assert_eq!(&with_nan[..2], &[-2.0, 5.0]);
+        assert!(with_nan[2].is_nan());
+    }
+
+    #[test]
+    fn median_component() {
+        assert_eq!(Vec3::new(3.0, -1.0, 2.0).median_component(), 2.0);
+        assert_eq!(Vec3::new(f32::NAN, 5.0, -2.0).median_component(), 5.0);
+    }
+
+    #[test]
+    fn snap_to_nearest_axis() {
+        assert_eq!(
+            Vec3::new(0.1, 0.9, -0.2).snap_to_nearest_axis(),
+            Vec3::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            Vec3::new(-5.0, 1.0, 1.0).snap_to_nearest_axis(),
+            Vec3::new(-1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            Vec3::new(0.3, 0.2, -0.4).snap_to_nearest_axis(),
+            Vec3::new(0.0, 0.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn reflect_about_direction() {
+        let a = Vec3::new(1.0, 1.0, 0.0);
+        let x_axis = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            a.reflect_about_direction(&x_axis),
+            Vec3::new(1.0, -1.0, 0.0)
+        );
+        assert_eq!(a.reflect_about_direction(&x_axis), -a.reflect(&x_axis));
+    }
+
+    #[test]
+    fn display() {
+        let a = Vec3::new(1.0, -2.5, 3.0);
+        assert_eq!(a.to_string(), "1, -2.5, 3");
+        assert_eq!(format!("{:.1}", a), "1.0, -2.5, 3.0");
+        assert_eq!(a.to_string().parse::<Vec3>(), Ok(a));
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "1.0, 2.0, 3.0".parse::<Vec3>(),
+            Ok(Vec3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!("1,2,3".parse::<Vec3>(), Ok(Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(" 1  2\t-3 ".parse::<Vec3>(), Ok(Vec3::new(1.0, 2.0, -3.0)));
+
+        assert_eq!(
+            "1.0, 2.0".parse::<Vec3>(),
+            Err(ParseVec3Error::WrongComponentCount(2))
+        );
+        assert!(matches!(
+            "1.0, two, 3.0".parse::<Vec3>(),
+            Err(ParseVec3Error::InvalidComponent(_))
+        ));
+    }
+
+    #[test]
+    fn checked_normalize() {
+        let a = Vec3::new(3.0, 0.0, 4.0);
+        assert_eq!(a.checked_normalize(), Ok(Vec3::new(0.6, 0.0, 0.8)));
+        assert_eq!(Vec3::ZERO.checked_normalize(), Err(NormalizeError));
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(4.0, 2.0, 4.0);
+        assert_eq!(Vec3::lerp(&a, &b, 0.0), a);
+        assert_eq!(Vec3::lerp(&a, &b, 1.0), b);
+        assert_eq!(Vec3::lerp(&a, &b, 0.25), Vec3::new(1.0, 2.0, -2.0));
+    }
+
+    #[test]
+    fn inverse_lerp() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(4.0, 2.0, 4.0);
+        let mid = Vec3::lerp(&a, &b, 0.5);
+        assert_eq!(Vec3::inverse_lerp(&a, &b, &mid), Vec3::new(0.5, 0.0, 0.5));
+
+        let a = Vec3::new(1.0, -3.0, 10.0);
+        let b = Vec3::new(3.0, 5.0, 20.0);
+        let value = Vec3::lerp(&a, &b, 0.75);
+        assert_eq!(Vec3::inverse_lerp(&a, &b, &value), Vec3::from_float(0.75));
+    }
+
+    #[test]
+    fn remap() {
+        let a = Vec3::new(0.0, 0.5, 1.0);
+        let remapped = a.remap(&Vec3::ZERO, &Vec3::ONE, &-Vec3::ONE, &Vec3::ONE);
+        assert_eq!(remapped, Vec3::new(-1.0, 0.0, 1.0));
+
+        // An empty input range maps to out_min
+        let empty = a.remap(&Vec3::ONE, &Vec3::ONE, &-Vec3::ONE, &Vec3::ONE);
+        assert_eq!(empty, -Vec3::ONE);
+    }
+
+    #[test]
+    fn nan_to_num() {
+        let a = Vec3::new(f32::NAN, f32::INFINITY, f32::NEG_INFINITY);
+        assert_eq!(a.nan_to_num(0.0, 1.0, -1.0), Vec3::new(0.0, 1.0, -1.0));
+
+        let finite = Vec3::new(1.0, -2.0, 3.0);
+        assert_eq!(finite.nan_to_num(0.0, 1.0, -1.0), finite);
+    }
+
+    #[test]
+    fn component_wrapped() {
+        let a = Vec3::new(3.0, 2.0, 1.0);
+        assert_eq!(a.component_wrapped(0), 3.0);
+        assert_eq!(a.component_wrapped(3), 3.0);
+        assert_eq!(a.component_wrapped(4), 2.0);
+        assert_eq!(a.component_wrapped(5), 1.0);
+    }
+
+    #[test]
+    fn interpolate_triangle() {
+        let a = Vec3::new(0.0, 0.0, 0.0);
+        let b = Vec3::new(3.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 3.0, 3.0);
+        let third = 1.0 / 3.0;
+        let centroid = Vec3::interpolate_triangle(&a, &b, &c, (third, third, third));
+        assert!((centroid - Vec3::new(1.0, 1.0, 1.0)).length() < 0.0001);
+        assert_eq!(Vec3::interpolate_triangle(&a, &b, &c, (0.0, 1.0, 0.0)), b);
+    }
+
+    #[test]
+    fn is_zero() {
+        assert!(Vec3::ZERO.is_zero());
+        assert!(Vec3::new(-0.0, 0.0, 0.0).is_zero());
+        assert!(!Vec3::new(0.0, 0.0001, 0.0).is_zero());
+    }
+
+    #[test]
+    fn is_approximately_zero() {
+        assert!(Vec3::ZERO.is_approximately_zero(0.001));
+        assert!(Vec3::new(0.0001, -0.0001, 0.0).is_approximately_zero(0.001));
+        assert!(!Vec3::new(1.0, 0.0, 0.0).is_approximately_zero(0.001));
+    }
+
+    #[test]
+    fn sign_bits() {
+        assert_eq!(Vec3::new(1.0, 2.0, 3.0).sign_bits(), 0);
+        assert_eq!(Vec3::new(-1.0, -2.0, -3.0).sign_bits(), 7);
+        assert_eq!(Vec3::new(1.0, -2.0, 3.0).sign_bits(), 0b010);
+        assert_eq!(Vec3::new(-1.0, 2.0, -3.0).sign_bits(), 0b101);
+    }
+
+    #[test]
+    fn project_onto_assign() {
+        let a = Vec3::new(3.0, 4.0, 5.0);
+        let b = Vec3::new(1.0, 1.0, 0.0);
+        let mut c = a;
+        c.project_onto_assign(&b);
+        assert_eq!(c, a.project_onto(&b));
+    }
+
+    #[test]
+    fn reflect_assign() {
+        let a = Vec3::new(1.0, -1.0, 0.5);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        let mut c = a;
+        c.reflect_assign(&normal);
+        assert_eq!(c, a.reflect(&normal));
+    }
+
+    #[test]
+    fn from_angle_xz() {
+        assert_eq!(Vec3::from_angle_xz(0.0), Vec3::new(1.0, 0.0, 0.0));
+        let quarter = Vec3::from_angle_xz(std::f32::consts::FRAC_PI_2);
+        assert!((quarter - Vec3::new(0.0, 0.0, 1.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn from_angle_xy() {
+        assert_eq!(Vec3::from_angle_xy(0.0), Vec3::new(1.0, 0.0, 0.0));
+        let quarter = Vec3::from_angle_xy(std::f32::consts::FRAC_PI_2);
+        assert!((quarter - Vec3::new(0.0, 1.0, 0.0)).length() < 0.0001);
+    }
+
+    #[test]
+    fn heading_xz() {
+        for &angle in &[0.0, 0.5, 2.0, -1.0, -3.0] {
+            let heading = Vec3::from_angle_xz(angle).heading_xz();
+            assert!((heading - angle).abs() < 0.0001);
         }
+        assert_eq!(Vec3::new(1.0, 5.0, 0.0).heading_xz(), 0.0);
     }
 
     #[test]
-    fn divide() {
-        let a = Vec3::new(1.0, 1.0, 2.0);
-        let b = Vec3::new(3.0, 4.0, 5.0);
-        assert_eq!(&a / &b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
-        assert_eq!(a / &b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
-        assert_eq!(&a / b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
-        assert_eq!(a / b, Vec3::new(1.0 / 3.0, 1.0 / 4.0, 2.0 / 5.0));
+    fn coordinates_in_basis() {
+        let a = Vec3::new(3.0, -2.0, 7.0);
+        let x = Vec3::from(Axis::X);
+        let y = Vec3::from(Axis::Y);
+        let z = Vec3::from(Axis::Z);
+        assert_eq!(a.coordinates_in_basis(&x, &y, &z), a);
+        assert_eq!(
+            a.coordinates_in_basis(&y, &z, &x),
+            Vec3::new(-2.0, 7.0, 3.0)
+        );
+    }
 
-        // Test for RHS value type
-        {
-            let mut c = Vec3::ONE;
-            c /= a;
-            assert_eq!(c, Vec3::new(1.0, 1.0, 0.5));
-        }
+    #[test]
+    fn clamp_abs() {
+        let a = Vec3::new(5.0, -5.0, 0.5);
+        assert_eq!(a.clamp_abs(&Vec3::ONE), Vec3::new(1.0, -1.0, 0.5));
+        assert_eq!(
+            a.clamp_abs(&Vec3::new(10.0, 2.0, 0.0)),
+            Vec3::new(5.0, -2.0, 0.0)
+        );
+    }
 
-        // Test for RHS borrowed reference
-        {
-            let mut c = Vec3::ONE;
-            c /= &a;
-            assert_eq!(c, Vec3::new(1.0, 1.0, 0.5));
+    #[test]
+    fn lerp_owned_and_borrowed() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(4.0, 2.0, 4.0);
+        let expected = Vec3::new(2.0, 2.0, 0.0);
+        assert_eq!(Vec3::lerp(a, b, 0.5), expected);
+        assert_eq!(Vec3::lerp(&a, b, 0.5), expected);
+        assert_eq!(Vec3::lerp(a, &b, 0.5), expected);
+        assert_eq!(Vec3::lerp(Vec3::ZERO, Vec3::ONE * 2.0, 0.5), Vec3::ONE);
+    }
+
+    #[test]
+    fn project_to_sphere() {
+        let center = Vec3::new(1.0, 1.0, 1.0);
+
+        let outside = Vec3::new(1.0, 5.0, 1.0);
+        assert_eq!(
+            outside.project_to_sphere(&center, 2.0),
+            Vec3::new(1.0, 3.0, 1.0)
+        );
+
+        let inside = Vec3::new(1.5, 1.0, 1.0);
+        assert_eq!(
+            inside.project_to_sphere(&center, 2.0),
+            Vec3::new(3.0, 1.0, 1.0)
+        );
+
+        let at_center = center.project_to_sphere(&center, 2.0);
+        assert_eq!((at_center - center).length(), 2.0);
+    }
+
+    #[test]
+    fn winding_sign() {
+        let a = Vec3::ZERO;
+        let b = Vec3::new(1.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+        assert_eq!(Vec3::winding_sign(&a, &b, &c, &z), 1.0);
+        assert_eq!(Vec3::winding_sign(&a, &c, &b, &z), -1.0);
+        assert_eq!(Vec3::winding_sign(&a, &b, &(b * 2.0), &z), 0.0);
+    }
+
+    #[test]
+    fn closest_point_on_triangle() {
+        let a = Vec3::ZERO;
+        let b = Vec3::new(2.0, 0.0, 0.0);
+        let c = Vec3::new(0.0, 2.0, 0.0);
+        let closest = |p: Vec3| Vec3::closest_point_on_triangle(&p, &a, &b, &c);
+
+        // Face region
+        assert_eq!(closest(Vec3::new(0.5, 0.5, 3.0)), Vec3::new(0.5, 0.5, 0.0));
+
+        // Edge regions
+        assert_eq!(closest(Vec3::new(1.0, -1.0, 1.0)), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(closest(Vec3::new(-1.0, 1.0, 0.0)), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(closest(Vec3::new(2.0, 2.0, 0.0)), Vec3::new(1.0, 1.0, 0.0));
+
+        // Vertex regions
+        assert_eq!(closest(Vec3::new(-1.0, -1.0, 0.0)), a);
+        assert_eq!(closest(Vec3::new(3.0, -1.0, 0.0)), b);
+        assert_eq!(closest(Vec3::new(-1.0, 3.0, 2.0)), c);
+    }
+
+    #[test]
+    fn slerp_long() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let nearly_opposite = Vec3::new(-1.0, 0.1, 0.0).normalize();
+
+        assert!((Vec3::slerp_long(&x, &nearly_opposite, 0.0) - x).length() < 0.0001);
+        assert!((Vec3::slerp_long(&x, &nearly_opposite, 1.0) - nearly_opposite).length() < 0.0001);
+
+        // The short path swings through +Y and the long path through -Y
+        let short_mid = Vec3::slerp(&x, &nearly_opposite, 0.5);
+        let long_mid = Vec3::slerp_long(&x, &nearly_opposite, 0.5);
+        assert!(short_mid.y > 0.99);
+        assert!(long_mid.y < -0.99);
+        assert!((long_mid + short_mid).length() < 0.0001);
+        assert!((long_mid.length() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn add_scaled_assign() {
+        let start = Vec3::new(1.0, 2.0, 3.0);
+        let velocity = Vec3::new(4.0, -2.0, 0.5);
+        let dt = 0.25;
+
+        let mut position = start;
+        position.add_scaled_assign(&velocity, dt);
+        assert_eq!(position, start + velocity * dt);
+
+        let mut position = start;
+        position.add_scaled_assign(&velocity, 0.1);
+        assert!((position - (start + velocity * 0.1)).length() < 0.0001);
+    }
+
+    #[test]
+    fn clamp_length_min() {
+        let short = Vec3::new(0.0, 0.3, 0.4);
+        assert!((short.clamp_length_min(2.0) - Vec3::new(0.0, 1.2, 1.6)).length() < 0.0001);
+
+        let long = Vec3::new(0.0, 3.0, 4.0);
+        assert_eq!(long.clamp_length_min(2.0), long);
+
+        assert_eq!(Vec3::ZERO.clamp_length_min(2.0), Vec3::ZERO);
+    }
+
+    #[test]
+    fn mean() {
+        assert_eq!(Vec3::from_float(2.0).mean(), 2.0);
+        assert_eq!(Vec3::new(1.0, 2.0, 6.0).mean(), 3.0);
+    }
+
+    #[test]
+    fn geometric_mean() {
+        assert!((Vec3::from_float(2.0).geometric_mean() - 2.0).abs() < 0.0001);
+        assert!((Vec3::new(1.0, 2.0, 4.0).geometric_mean() - 2.0).abs() < 0.0001);
+        assert_eq!(Vec3::new(0.0, 2.0, 4.0).geometric_mean(), 0.0);
+        assert_eq!(Vec3::new(-1.0, 1.0, 8.0).geometric_mean(), -2.0);
+
+        // The product of these would overflow or underflow an f32
+        let large = Vec3::from_float(1e13).geometric_mean();
+        assert!((large / 1e13 - 1.0).abs() < 0.0001);
+        let small = Vec3::from_float(1e-15).geometric_mean();
+        assert!((small / 1e-15 - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn to_degrees() {
+        let degrees = Vec3::from_float(std::f32::consts::PI).to_degrees();
+        assert!((degrees - Vec3::from_float(180.0)).length() < 0.001);
+    }
+
+    #[test]
+    fn to_radians() {
+        let radians = Vec3::new(180.0, 90.0, 0.0).to_radians();
+        let expected = Vec3::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_2, 0.0);
+        assert!((radians - expected).length() < 0.0001);
+    }
+
+    #[test]
+    fn trig() {
+        assert_eq!(Vec3::ZERO.sin(), Vec3::ZERO);
+        assert_eq!(Vec3::ZERO.cos(), Vec3::ONE);
+        assert_eq!(Vec3::ZERO.tan(), Vec3::ZERO);
+
+        let quarter = Vec3::new(
+            std::f32::consts::FRAC_PI_2,
+            0.0,
+            -std::f32::consts::FRAC_PI_2,
+        );
+        assert!((quarter.sin() - Vec3::new(1.0, 0.0, -1.0)).length() < 0.0001);
+        assert!((quarter.cos() - Vec3::new(0.0, 1.0, 0.0)).length() < 0.0001);
+        assert_eq!(quarter.sin_cos(), (quarter.sin(), quarter.cos()));
+    }
+
+    #[test]
+    fn sample_path() {
+        let path = [
+            Vec3::ZERO,
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 2.0, 0.0),
+        ];
+        assert_eq!(Vec3::sample_path(&path, 0.0), path[0]);
+        assert_eq!(Vec3::sample_path(&path, 1.0), path[2]);
+        assert_eq!(Vec3::sample_path(&path, 0.5), path[1]);
+        assert_eq!(Vec3::sample_path(&path, 0.75), Vec3::new(2.0, 1.0, 0.0));
+
+        // Clamped at the ends
+        assert_eq!(Vec3::sample_path(&path, -1.0), path[0]);
+        assert_eq!(Vec3::sample_path(&path, 2.0), path[2]);
+
+        // Degenerate paths
+        assert_eq!(Vec3::sample_path(&[Vec3::ONE], 0.5), Vec3::ONE);
+        assert_eq!(Vec3::sample_path(&[], 0.5), Vec3::ZERO);
+    }
+
+    #[test]
+    fn catmull_rom() {
+        let points = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 2.0, 0.0),
+            Vec3::new(3.0, 3.0, 1.0),
+            Vec3::new(4.0, 1.0, 2.0),
+            Vec3::new(6.0, 0.0, 2.0),
+        ];
+        let first = |t| Vec3::catmull_rom(&points[0], &points[1], &points[2], &points[3], t);
+        let second = |t| Vec3::catmull_rom(&points[1], &points[2], &points[3], &points[4], t);
+
+        assert_eq!(first(0.0), points[1]);
+        assert_eq!(first(1.0), points[2]);
+        assert_eq!(second(0.0), points[2]);
+
+        // The tangent leaving the first segment matches the tangent
+        // entering the second.
+        let h = 0.001;
+        let outgoing = (first(1.0) - first(1.0 - h)) / h;
+        let incoming = (second(h) - second(0.0)) / h;
+        assert!((outgoing - incoming).length() < 0.01);
+    }
+
+    #[test]
+    fn bezier_cubic() {
+        let p0 = Vec3::new(0.0, 0.0, 0.0);
+        let p1 = Vec3::new(0.0, 4.0, 0.0);
+        let p2 = Vec3::new(4.0, 4.0, 0.0);
+        let p3 = Vec3::new(4.0, 0.0, 8.0);
+        assert_eq!(Vec3::bezier_cubic(&p0, &p1, &p2, &p3, 0.0), p0);
+        assert_eq!(Vec3::bezier_cubic(&p0, &p1, &p2, &p3, 1.0), p3);
+
+        // At t = 0.5 the curve is (p0 + 3 p1 + 3 p2 + p3) / 8
+        assert_eq!(
+            Vec3::bezier_cubic(&p0, &p1, &p2, &p3, 0.5),
+            Vec3::new(2.0, 3.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn hash_to_unit() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(a.hash_to_unit(), Vec3::new(1.0, 2.0, 3.0).hash_to_unit());
+        assert_ne!(a.hash_to_unit(), Vec3::new(1.0, 2.0, 4.0).hash_to_unit());
+        assert_ne!(a.hash_to_unit(), Vec3::new(2.0, 1.0, 3.0).hash_to_unit());
+
+        for i in 0..100 {
+            let value = Vec3::new(i as f32, -(i as f32), 0.5).hash_to_unit();
+            assert!((0.0..1.0).contains(&value));
         }
     }
 
     #[test]
-    fn dot() {
-        let a = Vec3::new(2.0, 3.0, 5.0);
-        let b = Vec3::new(7.0, 11.0, 13.0);
-        assert_eq!(Vec3::dot(&a, &b), 2.0 * 7.0 + 3.0 * 11.0 + 5.0 * 13.0);
+    fn scalar_on_left() {
+        assert_eq!(2.0 / Vec3::new(1.0, 2.0, 4.0), Vec3::new(2.0, 1.0, 0.5));
+        assert_eq!(2.0 - Vec3::ONE, Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(2.0 + Vec3::new(1.0, 2.0, 4.0), Vec3::new(3.0, 4.0, 6.0));
+        assert_eq!(2.0 * Vec3::new(1.0, 2.0, 4.0), Vec3::new(2.0, 4.0, 8.0));
     }
 
     #[test]
-    fn cross() {
+    fn scalar_on_left_subtract() {
         let a = Vec3::new(1.0, 0.0, 0.0);
-        let b = Vec3::new(0.0, 1.0, 0.0);
-        assert_eq!(Vec3::cross(&a, &b), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(2.0 - a, Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(2.0 - &a, Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(a - 2.0, Vec3::new(-1.0, -2.0, -2.0));
     }
 
     #[test]
-    fn length() {
-        let a = Vec3::new(3.0, 2.0, 1.0);
-        assert_eq!(a.length(), ((3.0 * 3.0 + 2.0 * 2.0 + 1.0 * 1.0) as f32).sqrt());
+    fn scalar_on_left_divide() {
+        let a = Vec3::new(1.0, 4.0, -8.0);
+        assert_eq!(2.0 / a, Vec3::new(2.0, 0.5, -0.25));
+        assert_eq!(2.0 / &a, Vec3::new(2.0, 0.5, -0.25));
+        assert_eq!(a / 2.0, Vec3::new(0.5, 2.0, -4.0));
+    }
 
-        let b = Vec3::from_float(0.0);
-        assert_eq!(b.length(), 0.0);
+    #[test]
+    fn scalar_assign() {
+        let mut a = Vec3::new(1.0, 2.0, 3.0);
+        a *= 2.0;
+        assert_eq!(a, Vec3::new(2.0, 4.0, 6.0));
+        a += 1.0;
+        assert_eq!(a, Vec3::new(3.0, 5.0, 7.0));
+        a -= 3.0;
+        assert_eq!(a, Vec3::new(0.0, 2.0, 4.0));
+        a /= 2.0;
+        assert_eq!(a, Vec3::new(0.0, 1.0, 2.0));
     }
 
     #[test]
-    fn normalize() {
-        let a = Vec3::new(3.0, 2.0, 1.0);
-        let len = a.length();
-        assert!((a.normalize().length() - 1.0).abs() < 0.01);
-        assert_eq!(a.normalize(), a / len);
+    fn scale_axis() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(a.scale_axis(Axis::Y, 3.0), Vec3::new(1.0, 6.0, 3.0));
+        assert_eq!(a.scale_axis(Axis::X, -1.0), Vec3::new(-1.0, 2.0, 3.0));
     }
 
     #[test]
-    fn component() {
-        let a = Vec3::new(3.0, 2.0, 1.0);
-        assert_eq!(a.component(Axis::X), a.x);
-        assert_eq!(a.component(Axis::Y), a.y);
-        assert_eq!(a.component(Axis::Z), a.z);
+    fn collide() {
+        let velocity = Vec3::new(2.0, -3.0, 1.0);
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(
+            velocity.collide(&normal, 1.0, 0.0),
+            Vec3::new(2.0, 3.0, 1.0)
+        );
+        assert_eq!(
+            velocity.collide(&normal, 1.0, 1.0),
+            Vec3::new(0.0, 3.0, 0.0)
+        );
+        assert_eq!(
+            velocity.collide(&normal, 0.5, 0.5),
+            Vec3::new(1.0, 1.5, 0.5)
+        );
     }
 
     #[test]
-    fn set_component() {
-        let mut a = Vec3::new(3.0, 2.0, 1.0);
-        a.set_component(Axis::X, 4.0);
-        assert_eq!(a, Vec3::new(4.0, 2.0, 1.0));
+    fn quantize() {
+        let min = Vec3::new(-10.0, 0.0, -1.0);
+        let max = Vec3::new(10.0, 100.0, 1.0);
+        assert_eq!(min.quantize(&min, &max), [i16::MIN; 3]);
+        assert_eq!(max.quantize(&min, &max), [i16::MAX; 3]);
+
+        let step = (max - min) / 65535.0;
+        for a in &[
+            Vec3::new(1.234, 56.78, -0.9),
+            Vec3::new(-9.99, 0.01, 0.0),
+            Vec3::new(3.3333, 99.5, 0.123),
+        ] {
+            let round_trip = Vec3::dequantize(a.quantize(&min, &max), &min, &max);
+            let error = round_trip - a;
+            assert!(error.x.abs() < step.x && error.y.abs() < step.y && error.z.abs() < step.z);
+        }
+    }
 
-        a.set_component(Axis::Y, 5.0);
-        assert_eq!(a, Vec3::new(4.0, 5.0, 1.0));
+    #[test]
+    fn octahedral() {
+        let normals = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 2.0, 3.0).normalize(),
+            Vec3::new(-1.0, 0.5, -3.0).normalize(),
+            Vec3::new(0.3, -0.7, -0.2).normalize(),
+            Vec3::new(-0.6, -0.6, 0.1).normalize(),
+        ];
+        for n in &normals {
+            let (u, v) = n.encode_octahedral();
+            assert!(u.abs() <= 1.0 && v.abs() <= 1.0);
+            assert!((Vec3::decode_octahedral(u, v) - n).length() < 0.0001);
+        }
+    }
 
-        a.set_component(Axis::Z, 6.0);
-        assert_eq!(a, Vec3::new(4.0, 5.0, 6.0));
+    #[test]
+    fn fibonacci_sphere() {
+        let count = 64;
+        let mut sum = Vec3::ZERO;
+        for i in 0..count {
+            let point = Vec3::fibonacci_sphere(i, count);
+            assert!((point.length() - 1.0).abs() < 0.0001);
+            sum += point;
+        }
+
+        // Evenly spread points roughly cancel out
+        assert!((sum / count as f32).length() < 0.05);
+
+        let single = Vec3::fibonacci_sphere(0, 1);
+        assert!((single.length() - 1.0).abs() < 0.0001);
     }
 
     #[test]
-    fn with_component() {
-        let mut a = Vec3::new(3.0, 2.0, 1.0);
-        assert_eq!(a.with_x(4.0), Vec3::new(4.0, 2.0, 1.0));
-        assert_eq!(a.with_y(4.0), Vec3::new(3.0, 4.0, 1.0));
-        assert_eq!(a.with_z(4.0), Vec3::new(3.0, 2.0, 4.0));
+    fn gram_schmidt() {
+        let (u, v, w) = super::gram_schmidt(
+            Vec3::new(1.0, 0.01, 0.0),
+            Vec3::new(0.02, 1.0, 0.01),
+            Vec3::new(-0.01, 0.03, 0.98),
+        );
+        for axis in &[u, v, w] {
+            assert!((axis.length() - 1.0).abs() < 0.0001);
+        }
+        assert!(Vec3::dot(&u, &v).abs() < 0.0001);
+        assert!(Vec3::dot(&u, &w).abs() < 0.0001);
+        assert!(Vec3::dot(&v, &w).abs() < 0.0001);
+        assert_eq!(u, Vec3::new(1.0, 0.01, 0.0).normalize());
     }
 
     #[test]
-    fn min() {
-      let tiny_x = Vec3::new(0.00001, 1000.0, 1000.0);
-      let tiny_y = Vec3::new(1000.0, 0.00001, 1000.0);
-      let tiny_z = Vec3::new(1000.0, 1000.0, 0.00001);
-      assert_eq!(tiny_x.min(&tiny_y).min(&tiny_z), Vec3::from_float(0.00001));
+    fn all_comparisons() {
+        let small = Vec3::new(0.0, 1.0, 2.0);
+        let big = Vec3::new(1.0, 2.0, 3.0);
+        let mixed = Vec3::new(1.0, 0.0, 2.0);
+
+        assert!(small.all_lt(&big));
+        assert!(small.all_le(&big));
+        assert!(big.all_gt(&small));
+        assert!(big.all_ge(&small));
+
+        assert!(!small.all_lt(&mixed));
+        assert!(!small.all_le(&mixed));
+        assert!(!mixed.all_gt(&small));
+        assert!(!mixed.all_ge(&small));
+        assert!(small.all_le(&small));
+        assert!(!small.all_lt(&small));
     }
 
     #[test]
-    fn max() {
-      let big_x = Vec3::new(1000.0, 0.00001, 0.00001);
-      let big_y = Vec3::new(0.00001, 1000.0, 0.00001);
-      let big_z = Vec3::new(0.00001, 0.00001, 1000.0);
-      assert_eq!(big_x.max(&big_y).max(&big_z), Vec3::from_float(1000.0));
+    fn any_comparisons() {
+        let small = Vec3::new(0.0, 1.0, 2.0);
+        let big = Vec3::new(1.0, 2.0, 3.0);
+        let mixed = Vec3::new(1.0, 0.0, 2.0);
+
+        assert!(small.any_lt(&mixed));
+        assert!(small.any_gt(&mixed));
+        assert!(small.any_le(&mixed));
+        assert!(small.any_ge(&mixed));
+
+        assert!(!big.any_lt(&small));
+        assert!(!big.any_le(&small));
+        assert!(!small.any_gt(&big));
+        assert!(!small.any_ge(&big));
+    }
+
+    #[test]
+    fn aabb_overlap() {
+        let min_a = Vec3::ZERO;
+        let max_a = Vec3::from_float(2.0);
+
+        // Overlapping
+        assert!(Vec3::aabb_overlap(
+            &min_a,
+            &max_a,
+            &Vec3::ONE,
+            &Vec3::from_float(3.0)
+        ));
+
+        // Touching along a face
+        let touching_min = Vec3::new(2.0, 0.0, 0.0);
+        let touching_max = Vec3::new(4.0, 2.0, 2.0);
+        assert!(Vec3::aabb_overlap(
+            &min_a,
+            &max_a,
+            &touching_min,
+            &touching_max
+        ));
+
+        // Separated on one axis only
+        let separated_min = Vec3::new(0.0, 0.0, 2.5);
+        let separated_max = Vec3::new(2.0, 2.0, 4.0);
+        assert!(!Vec3::aabb_overlap(
+            &min_a,
+            &max_a,
+            &separated_min,
+            &separated_max
+        ));
+    }
+
+    #[test]
+    fn ray_sphere_intersect() {
+        let center = Vec3::new(0.0, 0.0, 10.0);
+        let forward = Vec3::new(0.0, 0.0, 1.0);
+
+        // Direct hit
+        assert_eq!(
+            Vec3::ray_sphere_intersect(&Vec3::ZERO, &forward, &center, 2.0),
+            Some(8.0)
+        );
+
+        // Tangent, grazing the side of the sphere
+        let grazing = Vec3::new(2.0, 0.0, 0.0);
+        assert_eq!(
+            Vec3::ray_sphere_intersect(&grazing, &forward, &center, 2.0),
+            Some(10.0)
+        );
+
+        // Miss, both to the side and with the sphere behind the ray
+        let beside = Vec3::new(3.0, 0.0, 0.0);
+        assert_eq!(
+            Vec3::ray_sphere_intersect(&beside, &forward, &center, 2.0),
+            None
+        );
+        assert_eq!(
+            Vec3::ray_sphere_intersect(&Vec3::ZERO, &-forward, &center, 2.0),
+            None
+        );
+
+        // Starting inside the sphere
+        assert_eq!(
+            Vec3::ray_sphere_intersect(&center, &forward, &center, 2.0),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn ray_plane_intersect() {
+        let plane_point = Vec3::new(0.0, 5.0, 0.0);
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let origin = Vec3::new(1.0, 1.0, 1.0);
+
+        assert_eq!(
+            Vec3::ray_plane_intersect(&origin, &up, &plane_point, &up),
+            Some(4.0)
+        );
+        assert_eq!(
+            Vec3::ray_plane_intersect(&origin, &-up, &plane_point, &up),
+            None
+        );
+
+        let sideways = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            Vec3::ray_plane_intersect(&origin, &sideways, &plane_point, &up),
+            None
+        );
+    }
+
+    #[test]
+    fn ray_triangle_intersect() {
+        let v0 = Vec3::new(-1.0, -1.0, 0.0);
+        let v1 = Vec3::new(2.0, -1.0, 0.0);
+        let v2 = Vec3::new(-1.0, 2.0, 0.0);
+        let origin = Vec3::new(0.0, 0.0, 5.0);
+        let down = Vec3::new(0.0, 0.0, -1.0);
+
+        // Through the centroid
+        let (t, u, v) = Vec3::ray_triangle_intersect(&origin, &down, &v0, &v1, &v2).unwrap();
+        assert!((t - 5.0).abs() < 0.0001);
+        assert!((u - 1.0 / 3.0).abs() < 0.0001);
+        assert!((v - 1.0 / 3.0).abs() < 0.0001);
+
+        // Misses off to the side
+        let beside = Vec3::new(3.0, 3.0, 5.0);
+        assert_eq!(
+            Vec3::ray_triangle_intersect(&beside, &down, &v0, &v1, &v2),
+            None
+        );
+
+        // Back face, triangle behind the ray, and parallel ray
+        let below = Vec3::new(0.0, 0.0, -5.0);
+        assert_eq!(
+            Vec3::ray_triangle_intersect(&below, &-down, &v0, &v1, &v2),
+            None
+        );
+        assert_eq!(
+            Vec3::ray_triangle_intersect(&below, &down, &v0, &v1, &v2),
+            None
+        );
+        let sideways = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            Vec3::ray_triangle_intersect(&origin, &sideways, &v0, &v1, &v2),
+            None
+        );
+    }
+
+    #[test]
+    fn rms() {
+        assert_eq!(Vec3::from_float(2.0).rms(), 2.0);
+        assert_eq!(Vec3::from_float(-2.0).rms(), 2.0);
+        assert!((Vec3::new(1.0, 2.0, 3.0).rms() - (14.0_f32 / 3.0).sqrt()).abs() < 0.0001);
+    }
+
+    #[test]
+    fn clamp_length_max() {
+        let long = Vec3::new(0.0, 3.0, 4.0);
+        assert_eq!(long.clamp_length_max(2.5), Vec3::new(0.0, 1.5, 2.0));
+
+        let short = Vec3::new(0.0, 0.3, 0.4);
+        assert_eq!(short.clamp_length_max(2.5), short);
+        assert_eq!(Vec3::ZERO.clamp_length_max(2.5), Vec3::ZERO);
+    }
+
+    #[test]
+    fn clamp_magnitude() {
+        let long = Vec3::new(0.0, 3.0, 4.0);
+        let short = Vec3::new(0.0, 0.3, 0.4);
+        assert_eq!(long.clamp_magnitude(2.5), long.clamp_length_max(2.5));
+        assert_eq!(short.clamp_magnitude(2.5), short.clamp_length_max(2.5));
+    }
+
+    #[test]
+    fn project_param_on_line() {
+        let point = Vec3::new(3.5, 2.0, -1.0);
+        let x_axis = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            Vec3::project_param_on_line(&point, &Vec3::ZERO, &x_axis),
+            3.5
+        );
+        assert_eq!(
+            Vec3::project_param_on_line(&point, &Vec3::ZERO, &(x_axis * 2.0)),
+            1.75
+        );
+    }
+
+    #[test]
+    fn mirror_across_line() {
+        let point = Vec3::new(1.0, 2.0, 3.0);
+        let x_axis = Vec3::new(1.0, 0.0, 0.0);
+        assert_eq!(
+            point.mirror_across_line(&Vec3::ZERO, &x_axis),
+            Vec3::new(1.0, -2.0, -3.0)
+        );
+
+        // A line parallel to X through (0, 1, 0)
+        let offset = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(
+            point.mirror_across_line(&offset, &x_axis),
+            Vec3::new(1.0, 0.0, -3.0)
+        );
+    }
+
+    #[test]
+    fn grid_cell() {
+        assert_eq!(Vec3::new(0.5, 1.5, 2.0).grid_cell(1.0), [0, 1, 2]);
+        assert_eq!(Vec3::new(1.99, 2.0, 2.01).grid_cell(2.0), [0, 1, 1]);
+        assert_eq!(Vec3::new(-0.01, -2.0, -2.01).grid_cell(2.0), [-1, -1, -2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_cell_zero_size() {
+        Vec3::ONE.grid_cell(0.0);
+    }
+
+    #[test]
+    fn pack_1010102() {
+        let normals = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 2.0, 3.0).normalize(),
+            Vec3::new(-1.0, 0.5, -3.0).normalize(),
+            Vec3::new(0.3, -0.7, -0.2).normalize(),
+        ];
+        for n in &normals {
+            let packed = n.pack_1010102();
+            assert_eq!(packed >> 30, 0);
+
+            let error = Vec3::unpack_1010102(packed) - n;
+            let tolerance = 0.5 / 511.0 + 0.000001;
+            assert!(error.x.abs() <= tolerance);
+            assert!(error.y.abs() <= tolerance);
+            assert!(error.z.abs() <= tolerance);
+        }
+    }
+
+    #[test]
+    fn permute() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(
+            a.permute(Axis::X, Axis::Z, Axis::Y),
+            Vec3::new(1.0, 3.0, 2.0)
+        );
+        assert_eq!(
+            a.permute(Axis::Z, Axis::X, Axis::Y),
+            Vec3::new(3.0, 1.0, 2.0)
+        );
+        assert_eq!(a.permute(Axis::X, Axis::X, Axis::X), Vec3::from_float(1.0));
+    }
+
+    #[test]
+    fn y_up_to_z_up() {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(up.y_up_to_z_up(), Vec3::new(0.0, 0.0, 1.0));
+
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(a.y_up_to_z_up(), Vec3::new(1.0, -3.0, 2.0));
+        assert_eq!(a.y_up_to_z_up().z_up_to_y_up(), a);
+        assert_eq!(a.z_up_to_y_up().y_up_to_z_up(), a);
+    }
+
+    #[test]
+    fn as_unit_axis() {
+        assert_eq!(
+            Vec3::new(1.0, 0.0, 0.0).as_unit_axis(),
+            Some((Axis::X, true))
+        );
+        assert_eq!(
+            Vec3::new(0.0, 0.0, -1.0).as_unit_axis(),
+            Some((Axis::Z, false))
+        );
+        assert_eq!(
+            Vec3::new(0.0, 1.000001, 0.0).as_unit_axis(),
+            Some((Axis::Y, true))
+        );
+        assert_eq!(Vec3::new(1.0, 1.0, 0.0).normalize().as_unit_axis(), None);
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0).as_unit_axis(), None);
+    }
+
+    #[test]
+    fn segment() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(3.0, 0.0, 3.0);
+        let segment = Segment::new(a, b);
+        assert_eq!(segment.at(0.0), a);
+        assert_eq!(segment.at(1.0), b);
+        assert_eq!(segment.at(0.5), Vec3::lerp(a, b, 0.5));
+    }
+
+    #[test]
+    fn cmp_by_length() {
+        let mut vectors = [
+            Vec3::new(0.0, 3.0, 0.0),
+            Vec3::new(f32::NAN, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+        ];
+        vectors.sort_by(|a, b| a.cmp_by_length(b));
+        assert_eq!(vectors[0], Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(vectors[1], Vec3::new(0.0, 0.0, 2.0));
+        assert_eq!(vectors[2], Vec3::new(0.0, 3.0, 0.0));
+        assert!(vectors[3].x.is_nan());
+    }
+
+    #[test]
+    fn clamp_scalar() {
+        let a = Vec3::new(-0.5, 0.25, 1.5);
+        assert_eq!(a.clamp_scalar(0.0, 1.0), Vec3::new(0.0, 0.25, 1.0));
+    }
+
+    #[test]
+    fn rotate_90() {
+        let x = Vec3::from(Axis::X);
+        let y = Vec3::from(Axis::Y);
+        let z = Vec3::from(Axis::Z);
+        assert_eq!(x.rotate_90(Axis::Z, 1), y);
+        assert_eq!(y.rotate_90(Axis::X, 1), z);
+        assert_eq!(z.rotate_90(Axis::Y, 1), x);
+        assert_eq!(x.rotate_90(Axis::Z, -1), -y);
+        assert_eq!(x.rotate_90(Axis::Z, 2), -x);
+
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        for axis in Axis::all().iter() {
+            assert_eq!(a.rotate_90(*axis, 4), a);
+            assert_eq!(a.rotate_90(*axis, 3), a.rotate_90(*axis, -1));
+        }
+    }
+
+    #[test]
+    fn hermite() {
+        let p0 = Vec3::new(0.0, 0.0, 0.0);
+        let p1 = Vec3::new(4.0, 8.0, -4.0);
+        let m0 = Vec3::new(1.0, 0.0, 0.0);
+        let m1 = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(Vec3::hermite(&p0, &m0, &p1, &m1, 0.0), p0);
+        assert_eq!(Vec3::hermite(&p0, &m0, &p1, &m1, 1.0), p1);
+
+        // Zero tangents give a smoothstep between the endpoints
+        for &t in &[0.25, 0.5, 0.75] {
+            let smoothstep = t * t * (3.0 - 2.0 * t);
+            let curve = Vec3::hermite(&p0, &Vec3::ZERO, &p1, &Vec3::ZERO, t);
+            assert!((curve - Vec3::lerp(p0, p1, smoothstep)).length() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn kahan_mean() {
+        let points = vec![Vec3::new(0.1, 1.1, -3.3); 1_000_000];
+
+        let mut naive_sum = Vec3::ZERO;
+        for point in &points {
+            naive_sum += point;
+        }
+        let naive_error = (naive_sum / points.len() as f32 - points[0]).length();
+        let kahan_error = (Vec3::kahan_mean(&points) - points[0]).length();
+        assert!(kahan_error < naive_error);
+        assert!(kahan_error < 0.00001);
+
+        assert_eq!(Vec3::kahan_mean(&[]), Vec3::ZERO);
+        assert_eq!(Vec3::kahan_mean(&[Vec3::ONE, -Vec3::ONE]), Vec3::ZERO);
+    }
+
+    #[test]
+    fn to_f64_array() {
+        let a = Vec3::new(0.1, -2.5, 1e30);
+        assert_eq!(a.to_f64_array(), [0.1_f32 as f64, -2.5, 1e30_f32 as f64]);
+    }
+
+    #[test]
+    fn from_f64_array() {
+        let a = Vec3::new(0.5, -2.25, 1024.0);
+        assert_eq!(Vec3::from_f64_array(a.to_f64_array()), a);
+        assert_eq!(Vec3::from_f64_array([0.1, 0.0, 0.0]).x, 0.1_f32);
+    }
+
+    #[test]
+    fn distance_squared_to_segment() {
+        let a = Vec3::ZERO;
+        let b = Vec3::new(4.0, 0.0, 0.0);
+        for p in &[
+            Vec3::new(2.0, 3.0, 0.0),
+            Vec3::new(-3.0, 4.0, 0.0),
+            Vec3::new(5.0, 1.0, 1.0),
+        ] {
+            let distance = (p - Vec3::closest_point_on_segment(p, &a, &b)).length();
+            let squared = Vec3::distance_squared_to_segment(p, &a, &b);
+            assert!((squared - distance * distance).abs() < 0.0001);
+        }
+        assert_eq!(
+            Vec3::distance_squared_to_segment(&Vec3::new(2.0, 3.0, 0.0), &a, &b),
+            9.0
+        );
+    }
+
+    #[test]
+    fn quantized_hash() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let nearby = Vec3::new(1.0003, 1.9998, 3.0001);
+        assert_eq!(a.quantized_hash(0.001), nearby.quantized_hash(0.001));
+        assert_ne!(
+            a.quantized_hash(0.001),
+            Vec3::new(1.0, 2.0, 3.1).quantized_hash(0.001)
+        );
+    }
+
+    #[test]
+    fn from_hex() {
+        assert_eq!(Vec3::from_hex("#FFFFFF"), Ok(Vec3::ONE));
+        assert_eq!(Vec3::from_hex("000000"), Ok(Vec3::ZERO));
+        assert_eq!(Vec3::from_hex("#ff0000"), Ok(Vec3::new(1.0, 0.0, 0.0)));
+
+        assert_eq!(Vec3::from_hex("#FFF"), Err(ParseHexError::InvalidLength(3)));
+        assert_eq!(Vec3::from_hex("#GG0000"), Err(ParseHexError::InvalidDigit));
+        assert_eq!(Vec3::from_hex("#ÿÿÿÿÿÿ"), Err(ParseHexError::InvalidDigit));
+    }
+
+    #[test]
+    fn to_hex() {
+        assert_eq!(Vec3::ONE.to_hex(), "#FFFFFF");
+        assert_eq!(Vec3::ZERO.to_hex(), "#000000");
+        assert_eq!(Vec3::new(1.0, 0.5, -1.0).to_hex(), "#FF8000");
+        assert_eq!(Vec3::from_hex("#12AB9F").unwrap().to_hex(), "#12AB9F");
+    }
+
+    #[test]
+    fn fresnel_split() {
+        let normal = Vec3::new(0.0, 0.0, 1.0);
+
+        // Normal incidence from air into glass passes straight through
+        let down = Vec3::new(0.0, 0.0, -1.0);
+        let (reflected, refracted, reflectance) = down.fresnel_split(&normal, 1.0 / 1.5);
+        assert_eq!(reflected, normal);
+        assert!((refracted.unwrap() - down).length() < 0.0001);
+        assert!((reflectance - 0.04).abs() < 0.0001);
+
+        // At 60 degrees, light inside glass is totally internally reflected
+        let angle = std::f32::consts::PI / 3.0;
+        let grazing = Vec3::new(angle.sin(), 0.0, -angle.cos());
+        let (reflected, refracted, reflectance) = grazing.fresnel_split(&normal, 1.5);
+        assert_eq!(reflected, grazing.reflect(&normal));
+        assert_eq!(refracted, None);
+        assert_eq!(reflectance, 1.0);
+
+        // The same angle from air into glass bends towards the normal
+        // following Snell's law
+        let (_, refracted, reflectance) = grazing.fresnel_split(&normal, 1.0 / 1.5);
+        let refracted = refracted.unwrap();
+        assert!((refracted.length() - 1.0).abs() < 0.0001);
+        assert!((refracted.x - angle.sin() / 1.5).abs() < 0.0001);
+        assert!(reflectance > 0.04 && reflectance < 1.0);
+    }
+
+    #[test]
+    fn variance() {
+        let points = [
+            Vec3::new(-1.0, 2.0, 5.0),
+            Vec3::new(1.0, -2.0, 5.0),
+            Vec3::new(-1.0, -2.0, 5.0),
+            Vec3::new(1.0, 2.0, 5.0),
+        ];
+        assert_eq!(Vec3::variance(&points), Vec3::new(1.0, 4.0, 0.0));
+        assert_eq!(Vec3::variance(&points[..1]), Vec3::ZERO);
+        assert_eq!(Vec3::variance(&[]), Vec3::ZERO);
+    }
+
+    #[test]
+    fn principal_axis() {
+        let direction = Vec3::new(1.0, 2.0, -0.5).normalize();
+        let side = Vec3::cross(&direction, &Vec3::new(0.0, 0.0, 1.0)).normalize();
+        let mut points = Vec::new();
+        for i in -10..=10 {
+            let t = i as f32;
+            let wobble = if i % 2 == 0 { 0.1 } else { -0.1 };
+            points.push(Vec3::new(3.0, -1.0, 2.0) + direction * t + side * wobble);
+        }
+
+        let axis = Vec3::principal_axis(&points);
+        assert!((axis.length() - 1.0).abs() < 0.0001);
+        assert!((Vec3::dot(&axis, &direction).abs() - 1.0).abs() < 0.0001);
+
+        assert_eq!(Vec3::principal_axis(&[Vec3::ONE]), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            Vec3::principal_axis(&[Vec3::ONE; 4]),
+            Vec3::new(1.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn swap_components() {
+        let mut a = Vec3::new(1.0, 2.0, 3.0);
+        a.swap_components(Axis::X, Axis::Z);
+        assert_eq!(a, Vec3::new(3.0, 2.0, 1.0));
+
+        a.swap_components(Axis::Y, Axis::Y);
+        assert_eq!(a, Vec3::new(3.0, 2.0, 1.0));
+    }
+
+    #[test]
+    fn normalize_or() {
+        let fallback = Vec3::new(1.0, 0.0, 0.0);
+        let a = Vec3::new(0.0, 3.0, 4.0);
+        assert_eq!(a.normalize_or(fallback), Vec3::new(0.0, 0.6, 0.8));
+        assert_eq!(Vec3::ZERO.normalize_or(fallback), fallback);
+        assert_eq!(
+            Vec3::new(f32::NAN, 1.0, 0.0).normalize_or(fallback),
+            fallback
+        );
+        assert_eq!(
+            Vec3::new(f32::INFINITY, 1.0, 0.0).normalize_or(fallback),
+            fallback
+        );
+
+        let huge = Vec3::new(0.0, 3e30, 4e30).normalize_or(fallback);
+        assert!((huge - Vec3::new(0.0, 0.6, 0.8)).length() < 0.0001);
+    }
+
+    #[test]
+    fn max_abs() {
+        let a = Vec3::new(-5.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, -4.0, -2.0);
+        assert_eq!(a.max_abs(&b), Vec3::new(-5.0, -4.0, 2.0));
+    }
+
+    #[test]
+    fn min_abs() {
+        let a = Vec3::new(-5.0, 1.0, 2.0);
+        let b = Vec3::new(3.0, -4.0, -2.0);
+        assert_eq!(a.min_abs(&b), Vec3::new(3.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn rotation_between() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+
+        let (axis, angle) = Vec3::rotation_between(&x, &y);
+        assert_eq!(axis, Vec3::new(0.0, 0.0, 1.0));
+        assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+
+        let (axis, angle) = Vec3::rotation_between(&x, &-x);
+        assert_eq!(angle, std::f32::consts::PI);
+        assert!((axis.length() - 1.0).abs() < 0.0001);
+        assert!(Vec3::dot(&axis, &x).abs() < 0.0001);
+
+        let (axis, angle) = Vec3::rotation_between(&y, &y);
+        assert_eq!(angle, 0.0);
+        assert!((axis.length() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn rotate_euler_xyz() {
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let x = Vec3::from(Axis::X);
+        let y = Vec3::from(Axis::Y);
+        let z = Vec3::from(Axis::Z);
+
+        // Single axis rotations
+        let about_z = x.rotate_euler_xyz(&Vec3::new(0.0, 0.0, quarter));
+        assert!((about_z - y).length() < 0.0001);
+        let about_x = y.rotate_euler_xyz(&Vec3::new(quarter, 0.0, 0.0));
+        assert!((about_x - z).length() < 0.0001);
+        let about_y = z.rotate_euler_xyz(&Vec3::new(0.0, quarter, 0.0));
+        assert!((about_y - x).length() < 0.0001);
+
+        // X is applied before Y: Y goes to Z about X, then Z goes to X
+        // about Y.
+        let compound = y.rotate_euler_xyz(&Vec3::new(quarter, quarter, 0.0));
+        assert!((compound - x).length() < 0.0001);
+    }
+
+    #[test]
+    fn lerp_samples() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(4.0, 2.0, 4.0);
+        assert_eq!(
+            Vec3::lerp_samples(&a, &b, 3),
+            vec![a, Vec3::new(2.0, 2.0, 0.0), b]
+        );
+        assert_eq!(Vec3::lerp_samples(&a, &b, 5).len(), 5);
+        assert_eq!(Vec3::lerp_samples(&a, &b, 1), vec![a]);
+        assert_eq!(Vec3::lerp_samples(&a, &b, 0), vec![]);
+    }
+
+    #[test]
+    fn split_direction_distance() {
+        let a = Vec3::new(0.0, 3.0, 4.0);
+        assert_eq!(
+            a.split_direction_distance(),
+            (Vec3::new(0.0, 0.6, 0.8), 5.0)
+        );
+        assert_eq!(Vec3::ZERO.split_direction_distance(), (Vec3::ZERO, 0.0));
+    }
+
+    #[test]
+    fn principal_axis_largest_column_is_minor_eigenvector() {
+        // The variance is 4.0 along (1, 1, 0) but only 3.38 along Z,
+        // even though the covariance column for Z is the longest.
+        let points = [
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.3),
+            Vec3::new(0.0, 0.0, -1.3),
+        ];
+        let axis = Vec3::principal_axis(&points);
+        let expected = Vec3::new(1.0, 1.0, 0.0).normalize();
+        assert!((Vec3::dot(&axis, &expected).abs() - 1.0).abs() < 0.0001);
     }
 }